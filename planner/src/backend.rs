@@ -0,0 +1,178 @@
+use crate::backend_html;
+use crate::backend_ics;
+use crate::backend_json;
+use crate::backend_mermaid;
+use crate::backend_plantuml;
+use crate::backend_svg;
+use crate::backend_table;
+use crate::cfg;
+use crate::gantt_builder;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone)]
+struct BackendError(String);
+
+impl std::fmt::Display for BackendError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "Backend error: {}", self.0)
+    }
+}
+
+impl std::error::Error for BackendError {}
+
+/// Rendering with a remote service is enabled by the `--api-server` flag
+/// or by `use_api` in the config
+pub fn use_api(cfg: &cfg::Config, api_server: bool) -> bool {
+    api_server || cfg.backend.plantuml.use_api
+}
+
+/// Resolves the `auto` format: plantuml if it can be used, svg otherwise
+pub fn resolve_format<'a>(cfg: &cfg::Config, format: &'a str, use_api: bool) -> &'a str {
+    if format != "auto" {
+        format
+    } else if use_api || backend_plantuml::local_installation_available(cfg) {
+        "plantuml"
+    } else {
+        "svg"
+    }
+}
+
+/// Where and how the output should be produced
+#[derive(Debug, Clone)]
+pub struct Output<'a> {
+    pub dir: &'a Path,
+    /// base name of the generated files (without extension)
+    pub name: &'a str,
+    /// render with a remote service if the backend supports it
+    pub use_api: bool,
+}
+
+impl Output<'_> {
+    /// Path of an output file with the given extension
+    pub fn file(&self, ext: &str) -> PathBuf {
+        let mut path = PathBuf::from(self.dir);
+        path.push(format!("{}.{ext}", self.name));
+        path
+    }
+}
+
+/// Renders the computed schedule into some output format
+pub trait Backend {
+    /// Name used for backend selection, e.g. `--format plantuml`
+    fn name(&self) -> &str;
+    fn render(
+        &self,
+        cfg: &cfg::Config,
+        data: &gantt_builder::GanttData,
+        out: &Output,
+    ) -> Result<(), Box<dyn std::error::Error>>;
+}
+
+/// Available backends, built-in ones are registered by default
+pub struct Registry {
+    backends: Vec<Box<dyn Backend>>,
+}
+
+impl Default for Registry {
+    fn default() -> Self {
+        let mut reg = Registry::empty();
+        reg.register(Box::new(backend_plantuml::PlantUml));
+        reg.register(Box::new(backend_svg::Svg));
+        reg.register(Box::new(backend_mermaid::Mermaid));
+        reg.register(Box::new(backend_html::Html));
+        reg.register(Box::new(backend_json::Json));
+        reg.register(Box::new(backend_table::Csv));
+        reg.register(Box::new(backend_table::Xlsx));
+        reg.register(Box::new(backend_ics::Ics));
+        reg
+    }
+}
+
+impl Registry {
+    pub fn empty() -> Registry {
+        Registry {
+            backends: Vec::new(),
+        }
+    }
+
+    /// Adds a backend, a backend with the same name is replaced
+    pub fn register(&mut self, backend: Box<dyn Backend>) {
+        self.backends.retain(|b| b.name() != backend.name());
+        self.backends.push(backend);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&dyn Backend> {
+        self.backends
+            .iter()
+            .find(|b| b.name() == name)
+            .map(|b| b.as_ref())
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        self.backends.iter().map(|b| b.name()).collect()
+    }
+
+    /// Renders the data with the backend selected by name
+    pub fn render(
+        &self,
+        name: &str,
+        cfg: &cfg::Config,
+        data: &gantt_builder::GanttData,
+        out: &Output,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        match self.get(name) {
+            Some(b) => b.render(cfg, data, out),
+            None => Err(Box::new(BackendError(format!(
+                "unknown format '{name}', available: {}",
+                self.names().join(", ")
+            )))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gantt_builder::tests::default_cfg;
+
+    struct Dummy;
+
+    impl Backend for Dummy {
+        fn name(&self) -> &str {
+            "plantuml"
+        }
+        fn render(
+            &self,
+            _cfg: &cfg::Config,
+            _data: &gantt_builder::GanttData,
+            _out: &Output,
+        ) -> Result<(), Box<dyn std::error::Error>> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_registry() {
+        let mut reg = Registry::default();
+        assert!(reg.get("plantuml").is_some());
+        assert!(reg.get("unknown").is_none());
+        reg.register(Box::new(Dummy));
+        assert_eq!(
+            reg.names(),
+            vec!["svg", "mermaid", "html", "json", "csv", "xlsx", "ics", "plantuml"]
+        );
+    }
+
+    #[test]
+    fn test_use_api() {
+        let mut cfg = default_cfg();
+        assert!(use_api(&cfg, false));
+        assert_eq!(
+            resolve_format(&cfg, "auto", use_api(&cfg, false)),
+            "plantuml"
+        );
+        cfg.backend.plantuml.use_api = false;
+        assert!(!use_api(&cfg, false));
+        assert!(use_api(&cfg, true));
+    }
+}
@@ -0,0 +1,398 @@
+use crate::backend;
+use crate::cfg;
+use crate::gantt_builder;
+use crate::project;
+use chrono::{Datelike, NaiveDate};
+
+/// Single, self-contained HTML report (no external resources)
+pub struct Html;
+
+impl backend::Backend for Html {
+    fn name(&self) -> &str {
+        "html"
+    }
+
+    fn render(
+        &self,
+        cfg: &cfg::Config,
+        data: &gantt_builder::GanttData,
+        out: &backend::Output,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        std::fs::write(out.file("html"), generate_html(cfg, data))?;
+        Ok(())
+    }
+}
+
+const STYLE: &str = r#"
+body { font-family: sans-serif; font-size: 13px; margin: 16px; }
+.toolbar { margin-bottom: 8px; }
+.toolbar button { min-width: 28px; }
+.scroll { overflow-x: auto; border: 1px solid #ccc; }
+.chart { --day: 20px; position: relative; }
+.row { display: flex; height: 24px; align-items: center; border-bottom: 1px solid #f0f0f0; }
+.label { position: sticky; left: 0; z-index: 2; flex: 0 0 240px; background: white;
+         white-space: nowrap; overflow: hidden; text-overflow: ellipsis; padding-left: 4px; }
+.track { position: relative; flex: 0 0 auto; height: 100%; }
+.cell { position: absolute; top: 0; height: 100%; width: var(--day); }
+.bar { position: absolute; top: 5px; height: 14px; border-radius: 3px; }
+.bar.paused { opacity: 0.25; }
+.bar.group { top: 8px; height: 8px; border-radius: 0; }
+details.group > summary { display: block; list-style: none; cursor: pointer; font-weight: bold; }
+details.group > summary::-webkit-details-marker { display: none; }
+details.group > summary .label::before { content: "\25BE  "; }
+details.group:not([open]) > summary .label::before { content: "\25B8  "; }
+.milestone { position: absolute; top: 6px; width: 12px; height: 12px; transform: rotate(45deg);
+             border: 1px solid #555; box-sizing: border-box; }
+.bar.shift { font-size: 10px; line-height: 14px; overflow: hidden; white-space: nowrap;
+             padding-left: 3px; box-sizing: border-box; }
+.head { font-size: 10px; text-align: center; line-height: 24px; overflow: hidden; }
+.heat { position: absolute; top: 3px; height: 18px; width: var(--day); box-sizing: border-box;
+        border-right: 1px solid white; }
+.marker { position: absolute; top: 0; bottom: 0; opacity: 0.4; pointer-events: none; }
+details.worker summary { cursor: pointer; }
+#tip { position: fixed; display: none; background: #333; color: white; padding: 6px 8px;
+       border-radius: 4px; white-space: pre; pointer-events: none; z-index: 10; }
+.legend span { display: inline-block; width: 14px; height: 14px; vertical-align: middle;
+               margin: 0 4px 0 12px; border: 1px solid #999; }
+"#;
+
+const SCRIPT: &str = r#"
+(function () {
+  var chart = document.querySelector('.chart');
+  var day = 20;
+  function zoom(f) {
+    day = Math.min(80, Math.max(3, day * f));
+    chart.style.setProperty('--day', day + 'px');
+  }
+  document.getElementById('zoom-in').onclick = function () { zoom(1.25); };
+  document.getElementById('zoom-out').onclick = function () { zoom(0.8); };
+  chart.addEventListener('wheel', function (e) {
+    if (e.ctrlKey) { e.preventDefault(); zoom(e.deltaY < 0 ? 1.1 : 0.9); }
+  }, { passive: false });
+  var tip = document.getElementById('tip');
+  document.addEventListener('mousemove', function (e) {
+    var t = e.target.closest('[data-tip]');
+    if (!t) { tip.style.display = 'none'; return; }
+    tip.textContent = t.getAttribute('data-tip');
+    tip.style.display = 'block';
+    tip.style.left = (e.clientX + 12) + 'px';
+    tip.style.top = (e.clientY + 12) + 'px';
+  });
+})();
+"#;
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Style placing an element on the timeline
+fn span(first: NaiveDate, from: NaiveDate, to: NaiveDate) -> String {
+    let left = (from - first).num_days();
+    let days = (to - from).num_days() + 1;
+    format!("left: calc({left} * var(--day)); width: calc({days} * var(--day));")
+}
+
+fn worker_day(cfg: &cfg::Config, day: gantt_builder::WorkerDay) -> (&str, &'static str) {
+    use gantt_builder::WorkerDay::*;
+    let c = &cfg.backend.colors;
+    match day {
+        PubHolidays => (&c.worker_pub_holidays, "PubHolidays"),
+        Holidays => (&c.worker_holidays, "Holidays"),
+        OtherDuties => (&c.worker_other_duties, "OtherDuties"),
+        Overloaded => (&c.worker_overloaded, "Overloaded"),
+        Underloaded => (&c.worker_underloaded, "Underloaded"),
+        Fine => (&c.worker_fine, "Fine"),
+        Unassigned => (&c.worker_unassigned, "Unassigned"),
+        PartialDay => (&c.worker_partial_day, "PartialDay"),
+    }
+}
+
+pub fn generate_html(cfg: &cfg::Config, data: &gantt_builder::GanttData) -> String {
+    let colors = &cfg.backend.colors;
+    let first = data.project_starts;
+    let mut last = first;
+    for t in &data.tasks {
+        last = last.max(t.end_on);
+    }
+    for g in &data.groups {
+        last = last.max(g.end_on);
+    }
+    for m in &data.milestones {
+        last = last.max(m.date);
+    }
+    for days in data.resource_allocation.0.values() {
+        if let Some((d, _)) = days.last_key_value() {
+            last = last.max(*d);
+        }
+    }
+    let days: Vec<NaiveDate> = first.iter_days().take_while(|d| *d <= last).collect();
+    let track = format!(
+        "<div class=\"track\" style=\"width: calc({} * var(--day));\">",
+        days.len()
+    );
+    let title = escape(&data.title);
+
+    let mut html = String::new();
+    html += "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n";
+    html += &format!("<title>{title}</title>\n<style>{STYLE}</style>\n</head>\n<body>\n");
+    html += &format!("<h1>{title}</h1>\n");
+    html += &format!(
+        "<p>Project starts on {} and ends on {}.</p>\n",
+        data.project_starts, data.project_ends
+    );
+    html += "<div class=\"toolbar\"><button id=\"zoom-out\">-</button> \
+             <button id=\"zoom-in\">+</button> (or Ctrl + mouse wheel)</div>\n";
+    html += "<div class=\"scroll\"><div class=\"chart\">\n";
+
+    // time axis
+    html += &format!("<div class=\"row\"><div class=\"label\"></div>{track}");
+    for d in &days {
+        let label = if d.day() == 1 || *d == first {
+            d.format("%d %b").to_string()
+        } else {
+            d.day().to_string()
+        };
+        html += &format!(
+            "<div class=\"cell head\" style=\"{}\" data-tip=\"{d} {}\">{label}</div>",
+            span(first, *d, *d),
+            d.weekday()
+        );
+    }
+    html += "</div></div>\n";
+
+    // closed days, public holidays and time markers in the background of tasks
+    let mut background = String::new();
+    for d in &days {
+        let shade = if data.public_holidays.contains(d) {
+            &colors.worker_pub_holidays
+        } else if data.closed_days.contains(&d.weekday()) {
+            "#eeeeee"
+        } else {
+            continue;
+        };
+        background += &format!(
+            "<div class=\"cell\" style=\"{} background: {shade}; opacity: 0.5;\"></div>",
+            span(first, *d, *d)
+        );
+    }
+    for tm in &data.time_markers {
+        let c = tm.color.as_ref().unwrap_or(&colors.time_markers);
+        for time in &tm.time {
+            let (from, to) = time.span();
+            if to < first || from > last {
+                continue;
+            }
+            background += &format!(
+                "<div class=\"marker\" style=\"{} background: {c};\"></div>",
+                span(first, from.max(first), to.min(last))
+            );
+        }
+    }
+    let list = |deps: &[project::Dependency]| {
+        if deps.is_empty() {
+            "-".to_string()
+        } else {
+            deps.iter()
+                .map(|d| d.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        }
+    };
+
+    // tasks, groups can be collapsed
+    let mut open_groups = 0;
+    for (depth, item) in data.outline() {
+        while open_groups > depth {
+            html += "</details>\n";
+            open_groups -= 1;
+        }
+        let indent = format!("padding-left: {}px;", 4 + 14 * depth);
+        let t = match item {
+            gantt_builder::OutlineItem::Group(g) => {
+                html += &format!(
+                    "<details class=\"group\" open><summary><div class=\"row\">\
+                     <div class=\"label\" style=\"{indent}\" title=\"{0}\">{0}</div>{track}{background}",
+                    escape(&g.name)
+                );
+                let tip = format!(
+                    "{}\nAfter: {}\nEstimate: {:.1} days\nStart: {}\nEnd: {}",
+                    g.name,
+                    list(&g.dependencies),
+                    g.estimate,
+                    g.start_on,
+                    g.end_on
+                );
+                let fill = if g.slack.critical {
+                    &colors.critical_path
+                } else {
+                    &colors.group
+                };
+                html += &format!(
+                    "<div class=\"bar group\" style=\"{} background: {fill};\" data-tip=\"{}\"></div>",
+                    span(first, g.start_on, g.end_on),
+                    escape(&tip)
+                );
+                html += "</div></div></summary>\n";
+                open_groups += 1;
+                continue;
+            }
+            gantt_builder::OutlineItem::Task(t) => t,
+        };
+        html += &format!(
+            "<div class=\"row\"><div class=\"label\" style=\"{indent}\" title=\"{0}\">{0}</div>{track}{background}",
+            escape(&format!("{} ({})", t.name, t.assignees.join(", ")))
+        );
+        let paused: Vec<String> = t.pause_days.iter().map(|d| d.to_string()).collect();
+        let tip = format!(
+            "{}\nAssignee: {}\nAfter: {}\nEstimate: {:.1} days\nFocus factor: {:.2}\nStart: {}\nEnd: {}\nPaused: {}",
+            t.name,
+            t.assignees.join(", "),
+            list(&t.dependencies),
+            t.estimate,
+            t.focus_factor,
+            t.start_on,
+            t.end_on,
+            if paused.is_empty() {
+                "-".to_string()
+            } else {
+                paused.join(", ")
+            }
+        );
+        let fill = if t.slack.critical {
+            &colors.critical_path
+        } else {
+            &colors.task
+        };
+        for d in t.start_on.iter_days().take_while(|d| *d <= t.end_on) {
+            let class = if t.pause_days.contains(&d) {
+                "bar paused"
+            } else {
+                "bar"
+            };
+            html += &format!(
+                "<div class=\"{class}\" style=\"{} background: {fill};\" data-tip=\"{}\"></div>",
+                span(first, d, d),
+                escape(&tip)
+            );
+        }
+        html += "</div></div>\n";
+    }
+    for _ in 0..open_groups {
+        html += "</details>\n";
+    }
+    // milestones
+    for m in &data.milestones {
+        html += &format!(
+            "<div class=\"row\"><div class=\"label\" title=\"{0}\">{0}</div>{track}",
+            escape(&m.name)
+        );
+        let mut tip = format!("{}\nForecast: {}", m.name, m.date);
+        if let Some(target) = m.target {
+            tip += &format!("\nTarget: {target}\nSlip: {} working days", m.slip_days);
+        }
+        let fill = if m.slack.critical {
+            &colors.critical_path
+        } else {
+            &colors.task
+        };
+        html += &format!(
+            "<div class=\"milestone\" style=\"left: calc({} * var(--day) + var(--day) / 2 - 6px); \
+             background: {fill};\" data-tip=\"{}\"></div>",
+            (m.date - first).num_days(),
+            escape(&tip)
+        );
+        html += "</div></div>\n";
+    }
+    // team rotations, one row per rotation
+    let mut rotations: Vec<&str> = data.rotations.iter().map(|s| s.rotation.as_str()).collect();
+    rotations.dedup();
+    for rotation in rotations {
+        html += &format!(
+            "<div class=\"row\"><div class=\"label\" title=\"{0}\">{0}</div>{track}",
+            escape(rotation)
+        );
+        for shift in data.rotations.iter().filter(|s| s.rotation == rotation) {
+            let (from, to) = (shift.from.max(first), shift.to.min(last));
+            if from > to {
+                continue;
+            }
+            html +=
+                &format!(
+                "<div class=\"bar shift\" style=\"{} background: {};\" data-tip=\"{}\">{}</div>",
+                span(first, from, to),
+                colors.rotation,
+                escape(&format!("{rotation}: {}\n{} - {}", shift.member, shift.from, shift.to)),
+                escape(&shift.member)
+            );
+        }
+        html += "</div></div>\n";
+    }
+    html += "</div></div>\n";
+
+    // resource heatmap
+    html += "<details open><summary><b>Resource allocation</b></summary>\n";
+    html += "<div class=\"scroll\"><div class=\"chart\">\n";
+    for (worker, alloc) in &data.resource_allocation.0 {
+        html += &format!(
+            "<details class=\"worker\" open><summary>{}</summary>\
+             <div class=\"row\"><div class=\"label\"></div>{track}",
+            escape(worker)
+        );
+        for (d, (hours, day)) in alloc {
+            let (c, kind) = worker_day(cfg, *day);
+            html += &format!(
+                "<div class=\"heat\" style=\"{} background: {c};\" data-tip=\"{} {d}\n{kind}: {:.1}h\"></div>",
+                span(first, *d, *d),
+                escape(worker),
+                hours.0
+            );
+        }
+        html += "</div></div></details>\n";
+    }
+    html += "</div></div>\n<p class=\"legend\">";
+    use gantt_builder::WorkerDay::*;
+    for day in [
+        PubHolidays,
+        Holidays,
+        OtherDuties,
+        Overloaded,
+        Underloaded,
+        Fine,
+        Unassigned,
+        PartialDay,
+    ] {
+        let (c, kind) = worker_day(cfg, day);
+        html += &format!("<span style=\"background: {c};\"></span>{kind}");
+    }
+    html += "</p>\n</details>\n";
+    html += &format!("<div id=\"tip\"></div>\n<script>{SCRIPT}</script>\n</body>\n</html>\n");
+    html
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gantt_builder::tests::{default_cfg, run_example};
+
+    #[test]
+    fn test_generate_html() {
+        let cfg = default_cfg();
+        let mut data = run_example("simple_project.toml");
+        data.rotations.push(gantt_builder::Shift {
+            rotation: "On-call".to_string(),
+            member: "Alicja".to_string(),
+            from: data.project_starts,
+            to: data.project_ends,
+        });
+        let html = generate_html(&cfg, &data);
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(!html.contains("http://") && !html.contains("https://"));
+        assert!(html.contains("Focus factor: 0.80"));
+        assert!(html.contains("<details class=\"worker\" open><summary>Alicja</summary>"));
+        assert!(html.contains("<div class=\"label\" title=\"On-call\">On-call</div>"));
+        assert!(html.contains("data-tip=\"On-call: Alicja\n"));
+    }
+}
@@ -0,0 +1,177 @@
+use crate::backend;
+use crate::cfg;
+use crate::gantt_builder;
+use chrono::{Days, NaiveDate, NaiveDateTime, Utc};
+
+/// One iCalendar file per team member with their tasks
+pub struct Ics;
+
+impl backend::Backend for Ics {
+    fn name(&self) -> &str {
+        "ics"
+    }
+
+    fn render(
+        &self,
+        cfg: &cfg::Config,
+        data: &gantt_builder::GanttData,
+        out: &backend::Output,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let now = Utc::now().naive_utc();
+        for worker in data.resource_allocation.0.keys() {
+            let ics = generate_ics(data, worker, cfg.backend.ics.include_absences, now);
+            let file_name: String = worker
+                .chars()
+                .map(|c| if c.is_alphanumeric() { c } else { '_' })
+                .collect();
+            std::fs::write(out.file(&format!("{file_name}.ics")), ics)?;
+        }
+        Ok(())
+    }
+}
+
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+/// Content lines longer than 75 octets have to be folded
+fn fold(line: &str) -> String {
+    let mut ret = String::new();
+    let mut len = 0;
+    for c in line.chars() {
+        if len + c.len_utf8() > 75 {
+            ret += "\r\n ";
+            len = 1;
+        }
+        ret.push(c);
+        len += c.len_utf8();
+    }
+    ret + "\r\n"
+}
+
+/// All-day events of a worker: (first day, last day, summary, uid)
+type Event = (NaiveDate, NaiveDate, String, String);
+
+/// Splits dates into runs of consecutive days
+fn consecutive(dates: &[NaiveDate]) -> Vec<(NaiveDate, NaiveDate)> {
+    let mut ret: Vec<(NaiveDate, NaiveDate)> = Vec::new();
+    for d in dates {
+        match ret.last_mut() {
+            Some((_, to)) if *to + Days::new(1) == *d => *to = *d,
+            _ => ret.push((*d, *d)),
+        }
+    }
+    ret
+}
+
+pub fn generate_ics(
+    data: &gantt_builder::GanttData,
+    worker: &str,
+    include_absences: bool,
+    dtstamp: NaiveDateTime,
+) -> String {
+    let mut events: Vec<Event> = Vec::new();
+    for t in data
+        .tasks
+        .iter()
+        .filter(|t| t.assignees.iter().any(|a| a == worker))
+    {
+        // task span without paused days
+        let days: Vec<NaiveDate> = t
+            .start_on
+            .iter_days()
+            .take_while(|d| *d <= t.end_on)
+            .filter(|d| !t.pause_days.contains(d))
+            .collect();
+        for (i, (from, to)) in consecutive(&days).into_iter().enumerate() {
+            let uid = format!("{}-{i}", t.id);
+            events.push((from, to, format!("{} [{}]", t.name, t.id), uid));
+        }
+    }
+    if include_absences {
+        use gantt_builder::WorkerDay;
+        let alloc = data.resource_allocation.0.get(worker);
+        for (kind, summary) in [
+            (WorkerDay::PubHolidays, "Public holiday"),
+            (WorkerDay::Holidays, "Holidays"),
+            (WorkerDay::OtherDuties, "Other duties"),
+        ] {
+            let days: Vec<NaiveDate> = alloc
+                .iter()
+                .flat_map(|a| a.iter())
+                .filter(|(_, (_, day))| *day == kind)
+                .map(|(d, _)| *d)
+                // non-working days are reported as public holidays too
+                .filter(|d| kind != WorkerDay::PubHolidays || data.public_holidays.contains(d))
+                .collect();
+            for (from, to) in consecutive(&days) {
+                let uid = format!("{kind:?}-{from}");
+                events.push((from, to, summary.to_string(), uid));
+            }
+        }
+    }
+    let worker_uid: String = worker
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .collect();
+    let mut ics = String::new();
+    ics += "BEGIN:VCALENDAR\r\n";
+    ics += "VERSION:2.0\r\n";
+    ics += "PRODID:-//planner//planner//EN\r\n";
+    ics += &fold(&format!(
+        "X-WR-CALNAME:{}",
+        escape(&format!("{} - {worker}", data.title))
+    ));
+    for (from, to, summary, uid) in events {
+        ics += "BEGIN:VEVENT\r\n";
+        ics += &fold(&format!("UID:{uid}-{worker_uid}@planner"));
+        ics += &format!("DTSTAMP:{}\r\n", dtstamp.format("%Y%m%dT%H%M%SZ"));
+        ics += &format!("DTSTART;VALUE=DATE:{}\r\n", from.format("%Y%m%d"));
+        // end date is exclusive
+        ics += &format!(
+            "DTEND;VALUE=DATE:{}\r\n",
+            (to + Days::new(1)).format("%Y%m%d")
+        );
+        ics += &fold(&format!("SUMMARY:{}", escape(&summary)));
+        ics += "TRANSP:TRANSPARENT\r\n";
+        ics += "END:VEVENT\r\n";
+    }
+    ics += "END:VCALENDAR\r\n";
+    ics
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gantt_builder::tests::run_example;
+
+    #[test]
+    fn test_generate_ics() {
+        let data = run_example("simple_project.toml");
+        let stamp = NaiveDate::from_ymd_opt(2024, 9, 1)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap();
+        let ics = generate_ics(&data, "Alicja", false, stamp);
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\n"));
+        assert!(ics.ends_with("END:VCALENDAR\r\n"));
+        // T2 is paused over the weekend and holidays: Oct 3-4 and Oct 9
+        assert!(ics.contains(
+            "DTSTART;VALUE=DATE:20241003\r\nDTEND;VALUE=DATE:20241005\r\nSUMMARY:Design DB schema [T2]"
+        ));
+        assert!(ics.contains(
+            "DTSTART;VALUE=DATE:20241009\r\nDTEND;VALUE=DATE:20241010\r\nSUMMARY:Design DB schema [T2]"
+        ));
+        assert!(!ics.contains("SUMMARY:Holidays"));
+        let ics = generate_ics(&data, "Alicja", true, stamp);
+        assert!(ics.contains(
+            "DTSTART;VALUE=DATE:20241007\r\nDTEND;VALUE=DATE:20241009\r\nSUMMARY:Holidays"
+        ));
+        assert!(ics.contains(
+            "DTSTART;VALUE=DATE:20241101\r\nDTEND;VALUE=DATE:20241102\r\nSUMMARY:Public holiday"
+        ));
+    }
+}
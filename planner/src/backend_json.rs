@@ -0,0 +1,69 @@
+use crate::backend;
+use crate::cfg;
+use crate::gantt_builder;
+use serde::Serialize;
+
+/// Version of the JSON schedule schema, bumped on incompatible changes
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Computed schedule as JSON, see README.md for the schema
+pub struct Json;
+
+#[derive(Serialize)]
+struct Schedule<'a> {
+    schema_version: u32,
+    #[serde(flatten)]
+    data: &'a gantt_builder::GanttData,
+}
+
+impl backend::Backend for Json {
+    fn name(&self) -> &str {
+        "json"
+    }
+
+    fn render(
+        &self,
+        _cfg: &cfg::Config,
+        data: &gantt_builder::GanttData,
+        out: &backend::Output,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        std::fs::write(out.file("json"), generate_json(data)?)?;
+        Ok(())
+    }
+}
+
+pub fn generate_json(
+    data: &gantt_builder::GanttData,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let schedule = Schedule {
+        schema_version: SCHEMA_VERSION,
+        data,
+    };
+    Ok(serde_json::to_string_pretty(&schedule)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gantt_builder::tests::run_example;
+
+    #[test]
+    fn test_generate_json() {
+        let data = run_example("complex_project.toml");
+        let json: serde_json::Value = serde_json::from_str(&generate_json(&data).unwrap()).unwrap();
+        assert_eq!(json["schema_version"], SCHEMA_VERSION);
+        assert_eq!(json["title"], "Game development");
+        assert_eq!(json["tasks"][0]["id"], "T1.0");
+        assert_eq!(json["tasks"][0]["start_on"], "2024-10-01");
+        assert_eq!(json["time_markers"][0]["time"][0], "2024-12-09");
+        assert_eq!(
+            json["resource_allocation"]["Alicja"]["2024-10-07"][1],
+            "Holidays"
+        );
+        // maps are written in the same order on every run
+        let out = generate_json(&data).unwrap();
+        let absence = &out[out.find("\"workers_absence\"").unwrap()..];
+        let pos = |name: &str| absence.find(&format!("\"{name}\"")).unwrap();
+        assert!(pos("Adam") < pos("Alicja") && pos("Alicja") < pos("Mirek"));
+    }
+}
@@ -0,0 +1,173 @@
+use crate::backend;
+use crate::cfg;
+use crate::gantt_builder;
+use crate::project::DependencyKind;
+use chrono::{Days, Weekday};
+use std::collections::{BTreeSet, HashMap};
+
+/// Mermaid `gantt` block, rendered inline by GitHub/GitLab markdown
+pub struct Mermaid;
+
+impl backend::Backend for Mermaid {
+    fn name(&self) -> &str {
+        "mermaid"
+    }
+
+    fn render(
+        &self,
+        _cfg: &cfg::Config,
+        data: &gantt_builder::GanttData,
+        out: &backend::Output,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let md = format!("```mermaid\n{}```\n", generate_mermaid(data));
+        std::fs::write(out.file("md"), md)?;
+        Ok(())
+    }
+}
+
+/// Mermaid ids can't contain dots and other special characters, so they are
+/// escaped as `_<hex code>_` and underscores doubled to keep ids distinct
+fn mermaid_id(id: &str) -> String {
+    id.chars()
+        .map(|c| match c {
+            c if c.is_ascii_alphanumeric() => c.to_string(),
+            '_' => "__".to_string(),
+            c => format!("_{:x}_", c as u32),
+        })
+        .collect()
+}
+
+/// Colons and hashes have a special meaning in the task line
+fn mermaid_text(s: &str) -> String {
+    s.replace('#', "#35;").replace(':', "#58;")
+}
+
+pub fn generate_mermaid(data: &gantt_builder::GanttData) -> String {
+    let mut ret = String::from("gantt\n");
+    ret += &format!("    title {}\n", mermaid_text(&data.title));
+    ret += "    dateFormat YYYY-MM-DD\n";
+    // excluded days
+    let mut excludes: Vec<String> = if data.closed_days == [Weekday::Sat, Weekday::Sun] {
+        vec!["weekends".into()]
+    } else {
+        data.closed_days
+            .iter()
+            .map(|d| format!("{d:?}").to_lowercase())
+            .map(|d| match d.as_str() {
+                "mon" => "monday".into(),
+                "tue" => "tuesday".into(),
+                "wed" => "wednesday".into(),
+                "thu" => "thursday".into(),
+                "fri" => "friday".into(),
+                "sat" => "saturday".into(),
+                _ => "sunday".into(),
+            })
+            .collect()
+    };
+    let holidays: BTreeSet<_> = data.public_holidays.iter().collect();
+    excludes.extend(holidays.iter().map(|d| d.to_string()));
+    if !excludes.is_empty() {
+        ret += &format!("    excludes {}\n", excludes.join(", "));
+    }
+    let ends: HashMap<_, _> = data.tasks.iter().map(|t| (&t.id, t.end_on)).collect();
+    // one section per group of tasks (or per assignee for tasks outside
+    // of groups), keeping the order of tasks
+    let section = |t: &gantt_builder::Task| match t.parent {
+        Some(_) => data
+            .ancestors(t.parent.as_ref())
+            .iter()
+            .map(|g| g.name.as_str())
+            .collect::<Vec<_>>()
+            .join(" / "),
+        None => t.assignee.clone(),
+    };
+    let mut sections: Vec<String> = Vec::new();
+    for t in &data.tasks {
+        let s = section(t);
+        if !sections.contains(&s) {
+            sections.push(s);
+        }
+    }
+    for name in sections {
+        ret += &format!("    section {}\n", mermaid_text(&name));
+        for t in data.tasks.iter().filter(|t| section(t) == name) {
+            let mut tags = String::new();
+            if t.slack.critical {
+                tags += "crit, ";
+            }
+            // use `after` when the task starts right after its dependencies,
+            // other dependency types and groups can't be expressed in mermaid
+            let deps_end = t.after.iter().filter_map(|a| ends.get(a)).max();
+            let finish_to_start = t.dependencies.iter().all(|d| {
+                d.kind == DependencyKind::FS && d.lag == 0.0 && ends.contains_key(&d.task)
+            });
+            let start = match deps_end {
+                Some(e) if finish_to_start && *e + Days::new(1) == t.start_on => {
+                    let after: Vec<_> = t.after.iter().map(|a| mermaid_id(a)).collect();
+                    format!("after {}", after.join(" "))
+                }
+                _ => t.start_on.to_string(),
+            };
+            // end date is exclusive in mermaid
+            ret += &format!(
+                "    {} :{tags}{}, {start}, {}\n",
+                mermaid_text(&t.name),
+                mermaid_id(&t.id),
+                t.end_on + Days::new(1)
+            );
+        }
+    }
+    // milestones and time markers
+    if !data.milestones.is_empty() || !data.time_markers.is_empty() {
+        ret += "    section Milestones\n";
+    }
+    for m in &data.milestones {
+        let crit = if m.slack.critical { "crit, " } else { "" };
+        ret += &format!(
+            "    {} :{crit}milestone, {}, {}, 0d\n",
+            mermaid_text(&m.name),
+            mermaid_id(&m.id),
+            m.date
+        );
+    }
+    for tm in &data.time_markers {
+        let label = mermaid_text(&tm.label);
+        for time in &tm.time {
+            match time.span() {
+                (f, t) if f == t => {
+                    ret += &format!("    {label} :milestone, {f}, 0d\n");
+                }
+                (f, t) => {
+                    ret += &format!("    {label} :active, {f}, {}\n", t + Days::new(1));
+                }
+            }
+        }
+    }
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gantt_builder::tests::run_example;
+
+    #[test]
+    fn test_generate_mermaid() {
+        let data = run_example("complex_project.toml");
+        let mermaid = generate_mermaid(&data);
+        let lines: Vec<_> = mermaid.lines().collect();
+        assert_eq!(lines[0], "gantt");
+        assert!(lines[3].starts_with("    excludes weekends, 2024-11-01"));
+        assert!(lines.contains(&"    section Mirek"));
+        assert!(lines.contains(&"    Assets units :T3_2e_2, after T3_2e_1, 2024-11-01"));
+        assert!(lines.contains(&"    Game exhibition :milestone, 2024-12-09, 0d"));
+    }
+
+    #[test]
+    fn test_mermaid_id() {
+        assert_eq!(mermaid_id("T1"), "T1");
+        assert_eq!(mermaid_id("T1.0"), "T1_2e_0");
+        assert_eq!(mermaid_id("T1_0"), "T1__0");
+        assert_ne!(mermaid_id("T1_2e_0"), mermaid_id("T1.0"));
+    }
+}
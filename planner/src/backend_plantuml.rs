@@ -1,7 +1,17 @@
-use crate::calendar;
+use crate::backend;
 use crate::cfg;
 use crate::gantt_builder;
+use crate::project::Dependency;
+use flate2::write::DeflateEncoder;
+use flate2::Compression;
+use std::io::{Read, Write};
 use std::process::{Command, Output};
+use std::time::Duration;
+
+/// GET requests longer than that are sent as POST
+const MAX_GET_URL_LEN: usize = 4096;
+const PLANTUML_ALPHABET: &[u8; 64] =
+    b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz-_";
 
 #[derive(Debug, Clone)]
 struct GenError(String);
@@ -14,6 +24,24 @@ impl std::fmt::Display for GenError {
 
 impl std::error::Error for GenError {}
 
+/// Gantt chart drawn by plantuml (local installation or server API)
+pub struct PlantUml;
+
+impl backend::Backend for PlantUml {
+    fn name(&self) -> &str {
+        "plantuml"
+    }
+
+    fn render(
+        &self,
+        cfg: &cfg::Config,
+        data: &gantt_builder::GanttData,
+        out: &backend::Output,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        build_chart(cfg, data, out.use_api, out.dir, out.name)
+    }
+}
+
 pub fn build_chart(
     cfg: &cfg::Config,
     data: &gantt_builder::GanttData,
@@ -22,15 +50,91 @@ pub fn build_chart(
     proj_name: &str,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let script = generate_plantuml_script(cfg, data)?;
+    let mut script_filename = std::path::PathBuf::from(out_dir);
+    script_filename.push(format!("{proj_name}.txt"));
     if api_server {
+        std::fs::write(&script_filename, &script)?;
+        let (image, ext) = render_with_api(cfg, &script)?;
+        let mut image_filename = std::path::PathBuf::from(out_dir);
+        image_filename.push(format!("{proj_name}.{ext}"));
+        std::fs::write(image_filename, image)?;
     } else {
-        let mut script_filename = std::path::PathBuf::from(out_dir);
-        script_filename.push(&format!("{proj_name}.txt"));
         generate_plantuml_diagram(cfg, out_dir, &script, &script_filename)?;
     }
     Ok(())
 }
 
+/// Encodes the script as expected by the PlantUML server:
+/// deflate compression followed by the PlantUML flavour of base64
+fn encode_plantuml(script: &str) -> Result<String, Box<dyn std::error::Error>> {
+    let mut encoder = DeflateEncoder::new(Vec::new(), Compression::best());
+    encoder.write_all(script.as_bytes())?;
+    let data = encoder.finish()?;
+    let mut ret = String::with_capacity(data.len() * 4 / 3 + 4);
+    for chunk in data.chunks(3) {
+        let b0 = chunk[0];
+        let b1 = chunk.get(1).copied().unwrap_or(0);
+        let b2 = chunk.get(2).copied().unwrap_or(0);
+        for c in [
+            b0 >> 2,
+            ((b0 & 0x3) << 4) | (b1 >> 4),
+            ((b1 & 0xf) << 2) | (b2 >> 6),
+            b2 & 0x3f,
+        ] {
+            ret.push(PLANTUML_ALPHABET[c as usize] as char);
+        }
+    }
+    Ok(ret)
+}
+
+/// Sends the script to the PlantUML server, returns the image
+/// and its extension (taken from the api_url, e.g. `.../png/`)
+fn render_with_api(
+    cfg: &cfg::Config,
+    script: &str,
+) -> Result<(Vec<u8>, String), Box<dyn std::error::Error>> {
+    let api_url = cfg.backend.plantuml.api_url.trim_end_matches('/');
+    let ext = match api_url.rsplit('/').next() {
+        Some("svg") => "svg",
+        _ => "png",
+    };
+    let agent = ureq::AgentBuilder::new()
+        .timeout(Duration::from_secs(cfg.backend.plantuml.api_timeout))
+        .build();
+    let url = format!("{api_url}/{}", encode_plantuml(script)?);
+    let response = if url.len() <= MAX_GET_URL_LEN {
+        agent.get(&url).call()
+    } else {
+        agent
+            .post(api_url)
+            .set("Content-Type", "text/plain")
+            .send_string(script)
+    };
+    let response = match response {
+        Ok(r) => r,
+        Err(ureq::Error::Status(code, r)) => {
+            let details = r
+                .header("X-PlantUML-Diagram-Error")
+                .unwrap_or(r.status_text())
+                .to_string();
+            return Err(report_err(format!(
+                "server {api_url} responded with {code}: {details}"
+            )));
+        }
+        Err(e) => return Err(report_err(format!("request to {api_url} failed: {e}"))),
+    };
+    let mut image = Vec::new();
+    response.into_reader().read_to_end(&mut image)?;
+    Ok((image, ext.into()))
+}
+
+/// Arrow drawing the dependency of task `id`, the same for every kind:
+/// constraints like "starts 2 days after" would make PlantUML move the bars
+/// away from the computed dates
+fn dependency(dep: &Dependency, id: &str) -> String {
+    format!("[{}] -> [{id}]\n", dep.task)
+}
+
 fn generate_plantuml_script(
     cfg: &cfg::Config,
     data: &gantt_builder::GanttData,
@@ -57,28 +161,88 @@ fn generate_plantuml_script(
     }
     // Project start date
     script += &format!("\nProject starts {}\n\n", data.project_starts);
-    // Task starting and finishing dates
-    for t in &data.tasks {
+    // Task starting and finishing dates, groups are separated and summarized
+    for (_, item) in data.outline() {
+        let t = match item {
+            gantt_builder::OutlineItem::Group(g) => {
+                let (name, id) = (&g.name, &g.id);
+                script += &format!("-- {name} --\n");
+                script += &format!(
+                    "[{name}] as [{id}] starts {} and ends {}\n",
+                    g.start_on, g.end_on
+                );
+                let c = if g.slack.critical {
+                    &cfg.backend.colors.critical_path
+                } else {
+                    &cfg.backend.colors.group
+                };
+                script += &format!("[{id}] is colored in {c}\n");
+                continue;
+            }
+            gantt_builder::OutlineItem::Task(t) => t,
+        };
         let name = &t.name;
         let id = &t.id;
-        let assignee = &t.assignee;
+        let assignees: Vec<String> = t.assignees.iter().map(|a| format!("{{{a}}}")).collect();
         script += &format!(
-            "[{name}] as [{id}] on {{{assignee}}} starts {}\n",
+            "[{name}] as [{id}] on {} starts {}\n",
+            assignees.join(" "),
             t.start_on
         );
         let end = t.end_on;
         script += &format!("[{id}] ends at {end}\n");
+        if t.slack.critical {
+            let c = &cfg.backend.colors.critical_path;
+            script += &format!("[{id}] is colored in {c}\n");
+        }
         // paused days
         for p in t.pause_days.iter() {
             script += &format!("[{id}] pauses on {p}\n");
         }
     }
+    // Milestones
+    for m in &data.milestones {
+        script += &format!("[{}] as [{}] happens on {}\n", m.name, m.id, m.date);
+        if m.slack.critical {
+            let c = &cfg.backend.colors.critical_path;
+            script += &format!("[{}] is colored in {c}\n", m.id);
+        }
+    }
     script += "\n";
     // Dependencies
-    for t in &data.tasks {
-        let id = &t.id;
-        for after in &t.after {
-            script += &format!("[{after}] -> [{id}]\n")
+    let deps = data
+        .tasks
+        .iter()
+        .map(|t| (&t.id, &t.dependencies))
+        .chain(data.groups.iter().map(|g| (&g.id, &g.dependencies)))
+        .chain(data.milestones.iter().map(|m| (&m.id, &m.dependencies)));
+    for (id, dependencies) in deps {
+        for dep in dependencies {
+            script += &dependency(dep, id);
+        }
+    }
+
+    // team rotations, one row per rotation
+    let mut rotations: Vec<&str> = data.rotations.iter().map(|s| s.rotation.as_str()).collect();
+    rotations.dedup();
+    for rotation in rotations {
+        script += &format!("-- {rotation} --\n");
+        let mut first: Option<String> = None;
+        for (i, shift) in data.rotations.iter().enumerate() {
+            if shift.rotation != rotation {
+                continue;
+            }
+            let alias = format!("rotation_{i}");
+            script += &format!(
+                "[{rotation}: {}] as [{alias}] starts {} and ends {}\n",
+                shift.member, shift.from, shift.to
+            );
+            script += &format!("[{alias}] is colored in {}\n", cfg.backend.colors.rotation);
+            if let Some(first) = &first {
+                script += &format!("[{alias}] displays on same row as [{first}]\n");
+            } else {
+                first = Some(alias);
+            }
         }
     }
 
@@ -98,6 +262,7 @@ fn generate_plantuml_script(
                 Underloaded => &cfg.backend.colors.worker_underloaded,
                 Fine => &cfg.backend.colors.worker_fine,
                 Unassigned => &cfg.backend.colors.worker_unassigned,
+                PartialDay => &cfg.backend.colors.worker_partial_day,
             };
             script += &format!("[{task_name}] is colored in {c}\n");
             if i > 0 {
@@ -109,22 +274,11 @@ fn generate_plantuml_script(
     // time markers
     for tm in &data.time_markers {
         for time in &tm.time {
-            let from;
-            let to;
-            match time {
-                calendar::DateObj::Date(d) => {
-                    from = d;
-                    to = d
-                }
-                calendar::DateObj::Range(f, t) => {
-                    from = f;
-                    to = t;
-                }
-            }
+            let (from, to) = time.span();
             let label = &tm.label;
             script += &format!("{from} to {to} are named [{label}]\n");
             let c = if let Some(ref c) = tm.color {
-                &c
+                c
             } else {
                 &cfg.backend.colors.time_markers
             };
@@ -158,6 +312,10 @@ fn generate_plantuml_script(
         "|<#{}>| Unassigned |\n",
         &cfg.backend.colors.worker_unassigned
     );
+    script += &format!(
+        "|<#{}>| PartialDay |\n",
+        &cfg.backend.colors.worker_partial_day
+    );
 
     script += "end legend\n";
 
@@ -165,6 +323,25 @@ fn generate_plantuml_script(
     Ok(script)
 }
 
+/// Checks if the local plantuml command can be run: the program
+/// starts and all referenced .jar files exist
+pub fn local_installation_available(cfg: &cfg::Config) -> bool {
+    let mut args = cfg
+        .backend
+        .plantuml
+        .local_cmd
+        .split(' ')
+        .filter(|a| !a.is_empty());
+    let Some(program) = args.next() else {
+        return false;
+    };
+    if Command::new(program).arg("-version").output().is_err() {
+        return false;
+    }
+    args.filter(|a| a.ends_with(".jar"))
+        .all(|jar| std::path::Path::new(jar).exists())
+}
+
 fn generate_plantuml_diagram(
     cfg: &cfg::Config,
     out_dir: &std::path::Path,
@@ -184,11 +361,14 @@ fn generate_plantuml_diagram(
             a
         }
     });
-    let command_result: Output =
-        Command::new(cmd_args.next().expect("invalid local_cmd config option"))
-            .args(cmd_args)
-            .output()
-            .expect("Failed to execute command");
+    let program = match cmd_args.next() {
+        Some(p) => p,
+        None => return Err(report_err("invalid local_cmd config option".into())),
+    };
+    let command_result: Output = Command::new(program)
+        .args(cmd_args)
+        .output()
+        .map_err(|e| report_err(format!("failed to execute '{program}': {e}")))?;
 
     // Get the exit status code
     let exit_code = command_result.status.code().unwrap_or(-1);
@@ -211,3 +391,85 @@ fn generate_plantuml_diagram(
 fn report_err(msg: String) -> Box<GenError> {
     Box::new(GenError(msg))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gantt_builder::tests::default_cfg;
+    use crate::project::DependencyKind;
+    use flate2::read::DeflateDecoder;
+    use std::net::TcpListener;
+
+    fn decode_plantuml(encoded: &str) -> String {
+        let sextets: Vec<u8> = encoded
+            .bytes()
+            .map(|c| PLANTUML_ALPHABET.iter().position(|a| *a == c).unwrap() as u8)
+            .collect();
+        let mut data = Vec::new();
+        for c in sextets.chunks(4) {
+            data.push((c[0] << 2) | (c[1] >> 4));
+            data.push((c[1] << 4) | (c[2] >> 2));
+            data.push((c[2] << 6) | c[3]);
+        }
+        let mut ret = String::new();
+        // trailing zero padding is ignored by the decoder
+        let _ = DeflateDecoder::new(&data[..]).read_to_string(&mut ret);
+        ret
+    }
+
+    #[test]
+    fn test_dependency() {
+        let dep = |task: &str, kind, lag| Dependency {
+            task: task.to_string(),
+            kind,
+            lag,
+        };
+        for (kind, lag) in [
+            (DependencyKind::FS, 0.0),
+            (DependencyKind::FS, -1.0),
+            (DependencyKind::SS, 2.0),
+            (DependencyKind::FF, 0.0),
+            (DependencyKind::SF, 1.0),
+        ] {
+            assert_eq!(dependency(&dep("A", kind, lag), "B"), "[A] -> [B]\n");
+        }
+    }
+
+    #[test]
+    fn test_encode_plantuml() {
+        let script = "@startgantt\n[Task] requires 5 days\n@endgantt\n";
+        let encoded = encode_plantuml(script).unwrap();
+        assert!(encoded.bytes().all(|c| PLANTUML_ALPHABET.contains(&c)));
+        assert_eq!(decode_plantuml(&encoded), script);
+    }
+
+    /// Serves a single HTTP response on a random local port
+    fn serve_once(response: &'static str) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut buf = [0; 4096];
+            let _ = stream.read(&mut buf);
+            stream.write_all(response.as_bytes()).unwrap();
+        });
+        format!("http://127.0.0.1:{port}/plantuml/svg/")
+    }
+
+    #[test]
+    fn test_render_with_api() {
+        let mut cfg = default_cfg();
+        cfg.backend.plantuml.api_url = serve_once(
+            "HTTP/1.1 200 OK\r\nContent-Type: image/svg+xml\r\nContent-Length: 6\r\n\r\n<svg/>",
+        );
+        let (image, ext) = render_with_api(&cfg, "@startgantt\n@endgantt\n").unwrap();
+        assert_eq!(image, b"<svg/>");
+        assert_eq!(ext, "svg");
+
+        cfg.backend.plantuml.api_url = serve_once(
+            "HTTP/1.1 400 Bad Request\r\nX-PlantUML-Diagram-Error: Syntax Error?\r\nContent-Length: 0\r\n\r\n",
+        );
+        let err = render_with_api(&cfg, "@startgantt\n@endgantt\n").unwrap_err();
+        assert!(err.to_string().contains("400: Syntax Error?"));
+    }
+}
@@ -0,0 +1,335 @@
+use crate::backend;
+use crate::cfg;
+use crate::gantt_builder;
+use crate::project::DependencyKind;
+use chrono::{Datelike, Days, NaiveDate};
+use std::collections::HashMap;
+
+const DAY_WIDTH: i64 = 18;
+const ROW_HEIGHT: i64 = 22;
+const LABEL_WIDTH: i64 = 220;
+const HEADER_HEIGHT: i64 = 76;
+const BAR_MARGIN: i64 = 4;
+
+/// Gantt chart drawn directly to SVG, no external tools required
+pub struct Svg;
+
+impl backend::Backend for Svg {
+    fn name(&self) -> &str {
+        "svg"
+    }
+
+    fn render(
+        &self,
+        cfg: &cfg::Config,
+        data: &gantt_builder::GanttData,
+        out: &backend::Output,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        std::fs::write(out.file("svg"), generate_svg(cfg, data))?;
+        Ok(())
+    }
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Time axis of the chart
+struct Timeline {
+    first: NaiveDate,
+    last: NaiveDate,
+}
+
+impl Timeline {
+    fn new(data: &gantt_builder::GanttData) -> Timeline {
+        let first = data.project_starts;
+        let mut last = first;
+        for t in &data.tasks {
+            last = last.max(t.end_on);
+        }
+        for m in &data.milestones {
+            last = last.max(m.date);
+        }
+        for days in data.resource_allocation.0.values() {
+            if let Some((d, _)) = days.last_key_value() {
+                last = last.max(*d);
+            }
+        }
+        Timeline { first, last }
+    }
+
+    fn days(&self) -> impl Iterator<Item = NaiveDate> + '_ {
+        self.first.iter_days().take_while(|d| *d <= self.last)
+    }
+
+    fn x(&self, d: NaiveDate) -> i64 {
+        LABEL_WIDTH + (d - self.first).num_days() * DAY_WIDTH
+    }
+
+    fn width(&self) -> i64 {
+        self.x(self.last) + DAY_WIDTH
+    }
+}
+
+fn rect(x: i64, y: i64, w: i64, h: i64, fill: &str, extra: &str) -> String {
+    format!("<rect x=\"{x}\" y=\"{y}\" width=\"{w}\" height=\"{h}\" fill=\"{fill}\"{extra}/>\n")
+}
+
+fn text(x: i64, y: i64, s: &str, extra: &str) -> String {
+    format!("<text x=\"{x}\" y=\"{y}\"{extra}>{}</text>\n", escape(s))
+}
+
+fn worker_day_color(cfg: &cfg::Config, day: gantt_builder::WorkerDay) -> &str {
+    use gantt_builder::WorkerDay::*;
+    let c = &cfg.backend.colors;
+    match day {
+        PubHolidays => &c.worker_pub_holidays,
+        Holidays => &c.worker_holidays,
+        OtherDuties => &c.worker_other_duties,
+        Overloaded => &c.worker_overloaded,
+        Underloaded => &c.worker_underloaded,
+        Fine => &c.worker_fine,
+        Unassigned => &c.worker_unassigned,
+        PartialDay => &c.worker_partial_day,
+    }
+}
+
+pub fn generate_svg(cfg: &cfg::Config, data: &gantt_builder::GanttData) -> String {
+    let colors = &cfg.backend.colors;
+    let tl = Timeline::new(data);
+    let workers = data.resource_allocation.0.len() as i64;
+    let outline = data.outline();
+    let tasks_height = (outline.len() + data.milestones.len()) as i64 * ROW_HEIGHT;
+    // tasks, section title and one row per worker
+    let chart_height = tasks_height + ROW_HEIGHT * (workers + 1);
+    let legend_y = HEADER_HEIGHT + chart_height + ROW_HEIGHT;
+    let height = legend_y + ROW_HEIGHT * 9;
+    let width = tl.width().max(LABEL_WIDTH + 200);
+
+    let mut svg = String::new();
+    svg += &format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" \
+         font-family=\"sans-serif\" font-size=\"11\">\n"
+    );
+    svg += "<defs><marker id=\"arrow\" viewBox=\"0 0 10 10\" refX=\"10\" refY=\"5\" \
+            markerWidth=\"6\" markerHeight=\"6\" orient=\"auto\">\
+            <path d=\"M 0 0 L 10 5 L 0 10 z\" fill=\"#555\"/></marker></defs>\n";
+    svg += &rect(0, 0, width, height, "white", "");
+    svg += &text(8, 18, &data.title, " font-size=\"16\" font-weight=\"bold\"");
+
+    // time axis, closed days and public holidays
+    let body_y = HEADER_HEIGHT;
+    for d in tl.days() {
+        let x = tl.x(d);
+        if d == tl.first || d.day() == 1 {
+            svg += &text(x + 2, 36, &d.format("%b %Y").to_string(), "");
+        }
+        svg += &text(x + 3, 56, &d.day().to_string(), " font-size=\"9\"");
+        if data.public_holidays.contains(&d) {
+            svg += &rect(x, body_y, DAY_WIDTH, chart_height, &colors.worker_pub_holidays, " opacity=\"0.4\"");
+        } else if data.closed_days.contains(&d.weekday()) {
+            svg += &rect(x, body_y, DAY_WIDTH, chart_height, "#eeeeee", "");
+        }
+    }
+    // time markers
+    for tm in &data.time_markers {
+        let c = tm.color.as_ref().unwrap_or(&colors.time_markers);
+        for time in &tm.time {
+            let (from, to) = time.span();
+            let from = from.max(tl.first);
+            let to = to.min(tl.last);
+            if from > to {
+                continue;
+            }
+            let w = tl.x(to) - tl.x(from) + DAY_WIDTH;
+            svg += &rect(tl.x(from), body_y, w, chart_height, c, " opacity=\"0.5\"");
+            svg += &text(tl.x(from) + 2, body_y - 4, &tm.label, " font-size=\"9\"");
+        }
+    }
+    // grid lines
+    svg += &format!(
+        "<line x1=\"0\" y1=\"{body_y}\" x2=\"{width}\" y2=\"{body_y}\" stroke=\"#999\"/>\n"
+    );
+
+    // tasks, subtasks are indented below the summary bars of their groups
+    let mut rows = HashMap::new();
+    for (i, (depth, item)) in outline.iter().enumerate() {
+        let y = body_y + i as i64 * ROW_HEIGHT;
+        let x = 8 + 12 * *depth as i64;
+        let t = match item {
+            gantt_builder::OutlineItem::Group(g) => {
+                rows.insert(&g.id, (i as i64, g.start_on, g.end_on));
+                svg += &text(x, y + 15, &g.name, " font-weight=\"bold\"");
+                let fill = if g.slack.critical {
+                    &colors.critical_path
+                } else {
+                    &colors.group
+                };
+                let w = tl.x(g.end_on) - tl.x(g.start_on) + DAY_WIDTH;
+                svg += &rect(tl.x(g.start_on), y + ROW_HEIGHT / 2 - 3, w, 6, fill, "");
+                continue;
+            }
+            gantt_builder::OutlineItem::Task(t) => t,
+        };
+        rows.insert(&t.id, (i as i64, t.start_on, t.end_on));
+        svg += &text(
+            x,
+            y + 15,
+            &format!("{} ({})", t.name, t.assignees.join(", ")),
+            "",
+        );
+        let fill = if t.slack.critical {
+            &colors.critical_path
+        } else {
+            &colors.task
+        };
+        // contiguous runs of working and paused days
+        let mut d = t.start_on;
+        while d <= t.end_on {
+            let paused = t.pause_days.contains(&d);
+            let mut to = d;
+            while to < t.end_on && t.pause_days.contains(&(to + Days::new(1))) == paused {
+                to = to + Days::new(1);
+            }
+            let extra = if paused { " opacity=\"0.25\"" } else { "" };
+            let w = tl.x(to) - tl.x(d) + DAY_WIDTH;
+            svg += &rect(tl.x(d), y + BAR_MARGIN, w, ROW_HEIGHT - 2 * BAR_MARGIN, fill, extra);
+            d = to + Days::new(1);
+        }
+    }
+    // milestones as diamonds below the tasks
+    for (i, m) in data.milestones.iter().enumerate() {
+        let row = (outline.len() + i) as i64;
+        let y = body_y + row * ROW_HEIGHT;
+        rows.insert(&m.id, (row, m.date, m.date));
+        let label = match m.target {
+            Some(target) => format!("{} (target: {target})", m.name),
+            None => m.name.clone(),
+        };
+        svg += &text(8, y + 15, &label, "");
+        let fill = if m.slack.critical {
+            &colors.critical_path
+        } else {
+            &colors.task
+        };
+        let (cx, cy, r) = (
+            tl.x(m.date) + DAY_WIDTH / 2,
+            y + ROW_HEIGHT / 2,
+            ROW_HEIGHT / 2 - 2,
+        );
+        svg += &format!(
+            "<polygon points=\"{cx},{} {},{cy} {cx},{} {},{cy}\" fill=\"{fill}\" stroke=\"#555\">\
+             <title>{}: {}</title></polygon>\n",
+            cy - r,
+            cx + r,
+            cy + r,
+            cx - r,
+            escape(&m.name),
+            m.date
+        );
+    }
+    // dependencies
+    let deps = data
+        .tasks
+        .iter()
+        .map(|t| (&t.id, &t.dependencies))
+        .chain(data.groups.iter().map(|g| (&g.id, &g.dependencies)))
+        .chain(data.milestones.iter().map(|m| (&m.id, &m.dependencies)));
+    for (id, dependencies) in deps {
+        let (row, start_on, end_on) = rows[id];
+        for dep in dependencies {
+            let Some((prev_row, prev_start, prev_end)) = rows.get(&dep.task) else {
+                continue;
+            };
+            // arrows go from the start or the end of the predecessor
+            // to the start or the end of the task
+            let (from_end, to_end) = match dep.kind {
+                DependencyKind::FS => (true, false),
+                DependencyKind::SS => (false, false),
+                DependencyKind::FF => (true, true),
+                DependencyKind::SF => (false, true),
+            };
+            let (x1, xm) = if from_end {
+                let x = tl.x(*prev_end) + DAY_WIDTH;
+                (x, x + DAY_WIDTH / 3)
+            } else {
+                let x = tl.x(*prev_start);
+                (x, x - DAY_WIDTH / 3)
+            };
+            let y1 = body_y + prev_row * ROW_HEIGHT + ROW_HEIGHT / 2;
+            let x2 = if to_end {
+                tl.x(end_on) + DAY_WIDTH
+            } else {
+                tl.x(start_on)
+            };
+            let y2 = body_y + row * ROW_HEIGHT + ROW_HEIGHT / 2;
+            svg += &format!(
+                "<path d=\"M {x1} {y1} H {xm} V {y2} H {x2}\" fill=\"none\" stroke=\"#555\" \
+                 marker-end=\"url(#arrow)\"/>\n"
+            );
+        }
+    }
+
+    // resource allocation
+    let mut y = body_y + tasks_height;
+    svg += &format!("<line x1=\"0\" y1=\"{y}\" x2=\"{width}\" y2=\"{y}\" stroke=\"#999\"/>\n");
+    svg += &text(8, y + 15, "Resource allocation", " font-weight=\"bold\"");
+    for (worker, days) in &data.resource_allocation.0 {
+        y += ROW_HEIGHT;
+        svg += &text(8, y + 15, worker, "");
+        for (d, (hours, day)) in days {
+            svg += &format!(
+                "<rect x=\"{}\" y=\"{}\" width=\"{DAY_WIDTH}\" height=\"{}\" fill=\"{}\" \
+                 stroke=\"white\"><title>{worker} {d}: {:.1}h</title></rect>\n",
+                tl.x(*d),
+                y + BAR_MARGIN,
+                ROW_HEIGHT - 2 * BAR_MARGIN,
+                worker_day_color(cfg, *day),
+                hours.0
+            );
+        }
+    }
+
+    // legend
+    use gantt_builder::WorkerDay::*;
+    let mut y = legend_y;
+    svg += &text(8, y, "Resource allocation legend:", " font-weight=\"bold\"");
+    for (day, label) in [
+        (PubHolidays, "PubHolidays"),
+        (Holidays, "Holidays"),
+        (OtherDuties, "OtherDuties"),
+        (Overloaded, "Overloaded"),
+        (Underloaded, "Underloaded"),
+        (Fine, "Fine"),
+        (Unassigned, "Unassigned"),
+        (PartialDay, "PartialDay"),
+    ] {
+        y += ROW_HEIGHT - 4;
+        svg += &rect(8, y - 11, 24, 14, worker_day_color(cfg, day), " stroke=\"#555\"");
+        svg += &text(40, y, label, "");
+    }
+    svg += "</svg>\n";
+    svg
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gantt_builder::tests::{default_cfg, run_example};
+
+    #[test]
+    fn test_generate_svg() {
+        let cfg = default_cfg();
+        let data = run_example("simple_project.toml");
+        let svg = generate_svg(&cfg, &data);
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.ends_with("</svg>\n"));
+        assert!(svg.contains("Design DB schema (Alicja)"));
+        // one arrow per dependency
+        assert_eq!(svg.matches("marker-end").count(), 4);
+        assert!(svg.contains(&cfg.backend.colors.worker_holidays));
+    }
+}
@@ -0,0 +1,222 @@
+use crate::backend;
+use crate::cfg;
+use crate::gantt_builder;
+
+/// Tasks and per-day allocation as two CSV files
+pub struct Csv;
+
+/// Tasks and per-day allocation as two sheets of an .xlsx workbook
+pub struct Xlsx;
+
+enum Cell {
+    Text(String),
+    Number(f64),
+}
+
+impl std::fmt::Display for Cell {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Cell::Text(s) => write!(f, "{s}"),
+            Cell::Number(n) => write!(f, "{n}"),
+        }
+    }
+}
+
+struct Table {
+    name: &'static str,
+    header: &'static [&'static str],
+    rows: Vec<Vec<Cell>>,
+}
+
+/// One row per scheduled task
+fn tasks_table(data: &gantt_builder::GanttData) -> Table {
+    let rows = data
+        .tasks
+        .iter()
+        .map(|t| {
+            let paused = t.pause_days.len() as f64;
+            let days = (t.end_on - t.start_on).num_days() as f64 + 1.0;
+            vec![
+                Cell::Text(t.id.clone()),
+                Cell::Text(t.name.clone()),
+                Cell::Text(t.assignees.join(", ")),
+                Cell::Text(t.start_on.to_string()),
+                Cell::Text(t.end_on.to_string()),
+                Cell::Number(days - paused),
+                Cell::Number(paused),
+                Cell::Number(t.estimate),
+            ]
+        })
+        .collect();
+    Table {
+        name: "tasks",
+        header: &[
+            "id",
+            "name",
+            "assignee",
+            "start",
+            "end",
+            "working_days",
+            "paused_days",
+            "estimate",
+        ],
+        rows,
+    }
+}
+
+/// One row per milestone
+fn milestones_table(data: &gantt_builder::GanttData) -> Table {
+    let rows = data
+        .milestones
+        .iter()
+        .map(|m| {
+            vec![
+                Cell::Text(m.id.clone()),
+                Cell::Text(m.name.clone()),
+                Cell::Text(m.date.to_string()),
+                Cell::Text(m.target.map(|t| t.to_string()).unwrap_or_default()),
+                Cell::Number(m.slip_days as f64),
+            ]
+        })
+        .collect();
+    Table {
+        name: "milestones",
+        header: &["id", "name", "date", "target", "slip_days"],
+        rows,
+    }
+}
+
+/// Tables of the schedule, milestones only if there are any
+fn tables(data: &gantt_builder::GanttData) -> Vec<Table> {
+    let mut tables = vec![tasks_table(data), allocation_table(data)];
+    if !data.milestones.is_empty() {
+        tables.push(milestones_table(data));
+    }
+    tables
+}
+
+/// One row per worker and day
+fn allocation_table(data: &gantt_builder::GanttData) -> Table {
+    let mut rows = Vec::new();
+    for (worker, days) in &data.resource_allocation.0 {
+        for (d, (hours, day)) in days {
+            rows.push(vec![
+                Cell::Text(worker.clone()),
+                Cell::Text(d.to_string()),
+                Cell::Number(hours.0),
+                Cell::Text(format!("{day:?}")),
+            ]);
+        }
+    }
+    Table {
+        name: "allocation",
+        header: &["worker", "date", "hours", "day_type"],
+        rows,
+    }
+}
+
+impl backend::Backend for Csv {
+    fn name(&self) -> &str {
+        "csv"
+    }
+
+    fn render(
+        &self,
+        _cfg: &cfg::Config,
+        data: &gantt_builder::GanttData,
+        out: &backend::Output,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        for table in tables(data) {
+            let path = out.file(&format!("{}.csv", table.name));
+            std::fs::write(path, generate_csv(&table)?)?;
+        }
+        Ok(())
+    }
+}
+
+fn generate_csv(table: &Table) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record(table.header)?;
+    for row in &table.rows {
+        writer.write_record(row.iter().map(|c| c.to_string()))?;
+    }
+    Ok(writer.into_inner()?)
+}
+
+impl backend::Backend for Xlsx {
+    fn name(&self) -> &str {
+        "xlsx"
+    }
+
+    fn render(
+        &self,
+        _cfg: &cfg::Config,
+        data: &gantt_builder::GanttData,
+        out: &backend::Output,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut workbook = rust_xlsxwriter::Workbook::new();
+        let bold = rust_xlsxwriter::Format::new().set_bold();
+        for table in tables(data) {
+            let sheet = workbook.add_worksheet();
+            sheet.set_name(table.name)?;
+            for (col, h) in table.header.iter().enumerate() {
+                sheet.write_string_with_format(0, col as u16, *h, &bold)?;
+            }
+            for (row, cells) in table.rows.iter().enumerate() {
+                let row = row as u32 + 1;
+                for (col, cell) in cells.iter().enumerate() {
+                    match cell {
+                        Cell::Text(s) => sheet.write_string(row, col as u16, s)?,
+                        Cell::Number(n) => sheet.write_number(row, col as u16, *n)?,
+                    };
+                }
+            }
+        }
+        workbook.save(out.file("xlsx"))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::Backend;
+    use crate::gantt_builder::tests::{default_cfg, run_example};
+
+    #[test]
+    fn test_generate_csv() {
+        let data = run_example("simple_project.toml");
+        let tasks = String::from_utf8(generate_csv(&tasks_table(&data)).unwrap()).unwrap();
+        let lines: Vec<_> = tasks.lines().collect();
+        assert_eq!(
+            lines[0],
+            "id,name,assignee,start,end,working_days,paused_days,estimate"
+        );
+        assert_eq!(
+            lines[2],
+            "T2,Design DB schema,Alicja,2024-10-03,2024-10-09,3,4,1.5"
+        );
+        let alloc = String::from_utf8(generate_csv(&allocation_table(&data)).unwrap()).unwrap();
+        assert!(alloc.contains("Alicja,2024-10-07,0,Holidays"));
+    }
+
+    #[test]
+    fn test_render_xlsx() {
+        let data = run_example("complex_project.toml");
+        let dir = std::env::temp_dir().join(format!("planner-xlsx-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let out = backend::Output {
+            dir: &dir,
+            name: "schedule",
+            use_api: false,
+        };
+        Xlsx.render(&default_cfg(), &data, &out).unwrap();
+        let bytes = std::fs::read(out.file("xlsx")).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(bytes.starts_with(b"PK"));
+        // zip entry names are stored uncompressed, one worksheet per table
+        let has = |name: &str| bytes.windows(name.len()).any(|w| w == name.as_bytes());
+        assert!(has("xl/worksheets/sheet3.xml"));
+        assert!(!has("xl/worksheets/sheet4.xml"));
+    }
+}
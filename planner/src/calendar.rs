@@ -1,13 +1,142 @@
+use crate::holidays::{self, HolidayRule};
+use crate::ical;
 use chrono::{Datelike, NaiveDate, Weekday};
-use serde::{self, Deserialize};
+use serde::{self, Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::Path;
 use toml;
 
 const DATE_FMT: &str = "%Y-%m-%d";
 
+pub const WEEKDAYS: [Weekday; 7] = [
+    Weekday::Mon,
+    Weekday::Tue,
+    Weekday::Wed,
+    Weekday::Thu,
+    Weekday::Fri,
+    Weekday::Sat,
+    Weekday::Sun,
+];
+
+#[derive(Debug, Clone)]
+struct CalendarError(String);
+
+impl std::fmt::Display for CalendarError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "Calendar error: {}", self.0)
+    }
+}
+
+impl std::error::Error for CalendarError {}
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum DateObj {
     Date(NaiveDate),
     Range(NaiveDate, NaiveDate),
+    /// Part of a day, e.g. `2024-12-24@4h` or `2024-12-24:am`
+    PartOfDay(NaiveDate, DayPart),
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum DayPart {
+    Hours(u32),
+    Am,
+    Pm,
+}
+
+impl DayPart {
+    /// Parses `am`, `pm` or hours like `4h`
+    pub fn parse(s: &str) -> Option<DayPart> {
+        match s.trim() {
+            "am" => Some(DayPart::Am),
+            "pm" => Some(DayPart::Pm),
+            h => h.strip_suffix('h')?.parse().ok().map(DayPart::Hours),
+        }
+    }
+
+    /// Hours of a working day of the given length taken by this part
+    pub fn hours(&self, day_len: u32) -> u32 {
+        match self {
+            DayPart::Hours(h) => (*h).min(day_len),
+            DayPart::Am => day_len / 2,
+            DayPart::Pm => day_len - day_len / 2,
+        }
+    }
+}
+
+impl DateObj {
+    /// First and last day, a part of a day counts as the whole day
+    pub fn span(&self) -> (NaiveDate, NaiveDate) {
+        match self {
+            DateObj::Date(d) | DateObj::PartOfDay(d, _) => (*d, *d),
+            DateObj::Range(f, t) => (*f, *t),
+        }
+    }
+}
+
+impl std::fmt::Display for DateObj {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            DateObj::Date(d) => write!(f, "{d}"),
+            DateObj::Range(from, to) => write!(f, "{from}:{to}"),
+            DateObj::PartOfDay(d, DayPart::Hours(h)) => write!(f, "{d}@{h}h"),
+            DateObj::PartOfDay(d, DayPart::Am) => write!(f, "{d}:am"),
+            DateObj::PartOfDay(d, DayPart::Pm) => write!(f, "{d}:pm"),
+        }
+    }
+}
+
+/// Serialized in the same format as it is parsed
+impl Serialize for DateObj {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// Date (or range) repeated every `interval` years,
+/// e.g. imported from an iCalendar RRULE
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct YearlyDate {
+    pub first: DateObj,
+    pub interval: u32,
+    /// number of occurrences
+    pub count: Option<u32>,
+    /// last possible start of an occurrence
+    pub until: Option<NaiveDate>,
+}
+
+impl YearlyDate {
+    /// First and last day of the occurrence starting in the given year
+    pub fn occurrence(&self, year: i32) -> Option<(NaiveDate, NaiveDate)> {
+        let (first, last) = self.first.span();
+        let interval = self.interval.max(1) as i32;
+        let n = year - first.year();
+        if n < 0 || n % interval != 0 || self.count.is_some_and(|c| (n / interval) as u32 >= c) {
+            return None;
+        }
+        // e.g. 29th of February doesn't occur every year
+        let start = first.with_year(year)?;
+        if self.until.is_some_and(|u| start > u) {
+            return None;
+        }
+        Some((start, start + (last - first)))
+    }
+
+    pub fn contains(&self, d: &NaiveDate) -> bool {
+        // an occurrence started in the previous year may last until now
+        [d.year() - 1, d.year()]
+            .into_iter()
+            .filter_map(|y| self.occurrence(y))
+            .any(|(start, end)| start <= *d && *d <= end)
+    }
+}
+
+/// Whole-day events of an iCalendar file
+pub fn load_ics(file: &Path) -> Result<Vec<ical::IcsEvent>, Box<dyn std::error::Error>> {
+    let contents = std::fs::read_to_string(file)
+        .map_err(|e| format!("can't read '{}': {e}", file.display()))?;
+    ical::parse(&contents)
 }
 
 #[derive(Debug, Deserialize)]
@@ -20,8 +149,28 @@ pub struct PublicHoliday {
 #[derive(Debug, Deserialize)]
 pub struct BusinessDaysCalendar {
     pub closed_days: Vec<Weekday>,
+    /// length of an ideal day of estimates, and working hours of a day
+    /// unless defined in `working_hrs_by_weekday`
     pub working_hrs_in_day: u32,
+    /// working hours of some week days, e.g. `{friday=6}`
+    #[serde(default)]
+    pub working_hrs_by_weekday: HashMap<Weekday, u32>,
+    #[serde(default)]
     pub public_holidays: Vec<PublicHoliday>,
+    /// iCalendar files with public holidays (relative to the calendar file)
+    #[serde(default)]
+    pub ics_files: Vec<String>,
+    /// built-in holiday rules of a country, see `holidays::builtin`
+    pub country: Option<String>,
+    /// public holidays repeated every year
+    #[serde(default)]
+    pub holiday_rules: Vec<HolidayRule>,
+    /// yearly holidays imported from `ics_files`
+    #[serde(skip)]
+    pub recurring_holidays: Vec<(YearlyDate, String)>,
+    /// `holiday_rules` expanded per year, filled on the first lookup
+    #[serde(skip)]
+    expanded_rules: RefCell<HashMap<i32, Vec<NaiveDate>>>,
 }
 
 pub fn parse_multidate_entry<'de, D>(deserializer: D) -> Result<Vec<DateObj>, D::Error>
@@ -31,7 +180,20 @@ where
     let s: String = String::deserialize(deserializer)?;
     let mut ret = Vec::new();
     for d in s.split(',').filter(|s| !s.trim().is_empty()) {
-        if let Some(range) = d.split_once(':') {
+        let parse_day = |d: &str| {
+            NaiveDate::parse_from_str(d.trim(), DATE_FMT).map_err(serde::de::Error::custom)
+        };
+        if let Some((day, hours)) = d.split_once('@') {
+            let hours = DayPart::parse(hours)
+                .filter(|p| matches!(p, DayPart::Hours(_)))
+                .ok_or_else(|| serde::de::Error::custom(format!("invalid hours in '{d}'")))?;
+            ret.push(DateObj::PartOfDay(parse_day(day)?, hours));
+        } else if let Some((day, part @ (DayPart::Am | DayPart::Pm))) = d
+            .split_once(':')
+            .and_then(|(day, part)| Some((day, DayPart::parse(part)?)))
+        {
+            ret.push(DateObj::PartOfDay(parse_day(day)?, part));
+        } else if let Some(range) = d.split_once(':') {
             let start_date =
                 NaiveDate::parse_from_str(range.0, DATE_FMT).map_err(serde::de::Error::custom)?;
             let end_date =
@@ -54,6 +216,13 @@ where
     NaiveDate::parse_from_str(&s, DATE_FMT).map_err(serde::de::Error::custom)
 }
 
+pub fn parse_opt_date_entry<'de, D>(deserializer: D) -> Result<Option<NaiveDate>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    parse_date_entry(deserializer).map(Some)
+}
+
 #[derive(Debug, PartialEq, Eq)]
 pub enum DayInfo {
     NonWorking,
@@ -61,31 +230,76 @@ pub enum DayInfo {
     WorkerHolidays,
     WorkerOtherDuties,
     WorkingDay(u32),
+    /// Working hours left after a part of the day is taken by absences
+    PartialDay(u32),
 }
 
 impl BusinessDaysCalendar {
     pub fn from(contents: &str) -> Result<BusinessDaysCalendar, Box<dyn std::error::Error>> {
-        let cal: Self = toml::from_str(contents)?;
+        let mut cal: Self = toml::from_str(contents)?;
+        if let Some(country) = &cal.country {
+            cal.holiday_rules.extend(holidays::builtin(country)?);
+        }
+        for rule in &cal.holiday_rules {
+            rule.validate()?;
+        }
+        if cal.working_hrs_in_day == 0 {
+            return Err(report_err("working_hrs_in_day must be more than 0".into()));
+        }
+        if WEEKDAYS
+            .iter()
+            .all(|d| cal.closed_days.contains(d) || cal.working_hrs(*d) == 0)
+        {
+            return Err(report_err("no working hours on any open week day".into()));
+        }
         Ok(cal)
     }
 
-    pub fn year_covered(&self, year: u32) -> bool {
-        let year = year as i32;
-        for h in self.public_holidays.iter().flat_map(|h| &h.date) {
-            match h {
-                DateObj::Date(d) => {
-                    if d.year() == year {
-                        return true;
-                    }
-                }
-                DateObj::Range(f, t) => {
-                    if f.year() <= year || t.year() >= year {
-                        return true;
-                    }
+    /// Imports public holidays from `ics_files`
+    pub fn load_ics(&mut self, base_dir: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        for file in &self.ics_files {
+            for event in load_ics(&base_dir.join(file))? {
+                match event.date {
+                    ical::IcsDate::Once(d) => self.public_holidays.push(PublicHoliday {
+                        date: vec![d],
+                        name: event.summary,
+                    }),
+                    ical::IcsDate::Yearly(y) => self.recurring_holidays.push((y, event.summary)),
                 }
             }
         }
-        false
+        Ok(())
+    }
+
+    /// Holidays defined by `holiday_rules` in the given year, rules are
+    /// expanded once per year
+    fn rule_holidays<T>(&self, year: i32, f: impl FnOnce(&[NaiveDate]) -> T) -> T {
+        let mut cache = self.expanded_rules.borrow_mut();
+        let holidays = cache.entry(year).or_insert_with(|| {
+            holidays::expand(&self.holiday_rules, year)
+                .into_iter()
+                .map(|(h, _)| h)
+                .collect()
+        });
+        f(holidays)
+    }
+
+    /// Whether public holidays are defined for the given year
+    pub fn year_covered(&self, year: i32) -> bool {
+        if self.rule_holidays(year, |h| !h.is_empty()) {
+            return true;
+        }
+        if self
+            .recurring_holidays
+            .iter()
+            .any(|(y, _)| y.occurrence(year).is_some())
+        {
+            return true;
+        }
+        self.public_holidays.iter().flat_map(|h| &h.date).any(|h| {
+            let (f, t) = h.span();
+            f.year() <= year && t.year() >= year
+        })
     }
 
     pub fn day_info(&self, d: &NaiveDate) -> DayInfo {
@@ -93,25 +307,61 @@ impl BusinessDaysCalendar {
             return DayInfo::NonWorking;
         }
         // dummy & inneficient but simple: iterate over all holidays
-        for h in self.public_holidays.iter().flat_map(|h| &h.date) {
-            match h {
-                DateObj::Date(dd) => {
-                    if dd == d {
-                        return DayInfo::NonWorkingPubHoliday;
-                    }
-                }
-                DateObj::Range(f, t) => {
-                    if f <= d || t >= d {
-                        return DayInfo::NonWorkingPubHoliday;
-                    }
-                }
+        for h in self.public_holidays.iter() {
+            if in_date_obj_vec(d, &h.date) {
+                return DayInfo::NonWorkingPubHoliday;
             }
         }
+        if self.recurring_holidays.iter().any(|(y, _)| y.contains(d)) {
+            return DayInfo::NonWorkingPubHoliday;
+        }
+        // holidays of the next year may be observed at the end of this one
+        if [d.year(), d.year() + 1]
+            .iter()
+            .any(|year| self.rule_holidays(*year, |h| h.contains(d)))
+        {
+            return DayInfo::NonWorkingPubHoliday;
+        }
 
-        DayInfo::WorkingDay(self.working_hrs_in_day)
+        let hrs = self.working_hrs(d.weekday());
+        let off = self.partial_holiday_hrs(d, hrs);
+        match (hrs, off) {
+            (0, _) => DayInfo::NonWorking,
+            (h, 0) => DayInfo::WorkingDay(h),
+            (h, off) if off >= h => DayInfo::NonWorkingPubHoliday,
+            (h, off) => DayInfo::PartialDay(h - off),
+        }
     }
+
+    /// Hours of a working day of the given length taken by public holidays
+    /// lasting a part of the day
+    pub fn partial_holiday_hrs(&self, d: &NaiveDate, day_len: u32) -> u32 {
+        self.public_holidays
+            .iter()
+            .map(|h| partial_day_hrs(d, &h.date, day_len))
+            .sum()
+    }
+
+    pub fn working_hrs(&self, weekday: Weekday) -> u32 {
+        *self
+            .working_hrs_by_weekday
+            .get(&weekday)
+            .unwrap_or(&self.working_hrs_in_day)
+    }
+}
+
+/// Hours of a working day of the given length taken by parts of the day
+pub fn partial_day_hrs(d: &NaiveDate, dates: &[DateObj], day_len: u32) -> u32 {
+    dates
+        .iter()
+        .map(|dt| match dt {
+            DateObj::PartOfDay(dd, part) if dd == d => part.hours(day_len),
+            _ => 0,
+        })
+        .sum()
 }
 
+/// Whether the whole day `d` is included in `dates`
 pub fn in_date_obj_vec(d: &NaiveDate, dates: &[DateObj]) -> bool {
     for dt in dates.iter() {
         match dt {
@@ -123,6 +373,10 @@ pub fn in_date_obj_vec(d: &NaiveDate, dates: &[DateObj]) -> bool {
     false
 }
 
+fn report_err(msg: String) -> Box<CalendarError> {
+    Box::new(CalendarError(msg))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -135,10 +389,108 @@ mod tests {
         }
         assert!(cal.is_ok());
         let cal = cal.unwrap();
-        assert_eq!(cal.public_holidays.len(), 26);
+        assert_eq!(cal.holiday_rules.len(), 14);
+        // dates which used to be listed by hand
+        for d in [
+            "2024-01-01",
+            "2024-01-06",
+            "2024-03-31",
+            "2024-04-01",
+            "2024-05-01",
+            "2024-05-03",
+            "2024-05-19",
+            "2024-05-30",
+            "2024-08-15",
+            "2024-11-01",
+            "2024-11-11",
+            "2024-12-25",
+            "2024-12-26",
+            "2025-01-01",
+            "2025-01-06",
+            "2025-04-20",
+            "2025-04-21",
+            "2025-05-01",
+            "2025-05-03",
+            "2025-06-08",
+            "2025-06-19",
+            "2025-08-15",
+            "2025-11-01",
+            "2025-11-11",
+            "2025-12-24",
+            "2025-12-25",
+            "2025-12-26",
+        ] {
+            let d = NaiveDate::parse_from_str(d, DATE_FMT).unwrap();
+            assert_ne!(cal.day_info(&d), DayInfo::WorkingDay(8), "{d}");
+        }
+        let christmas_eve = NaiveDate::parse_from_str("2024-12-24", DATE_FMT).unwrap();
+        assert_eq!(cal.day_info(&christmas_eve), DayInfo::WorkingDay(8));
+    }
+
+    #[test]
+    fn test_day_info_ranges() {
+        let date = |s| NaiveDate::parse_from_str(s, DATE_FMT).unwrap();
+        let mut cal = BusinessDaysCalendar::from(
+            "closed_days = [\"saturday\", \"sunday\"]\n\
+             working_hrs_in_day = 8\n\
+             working_hrs_by_weekday = {friday=6, thursday=0}\n\
+             public_holidays = [{date=\"2024-12-24:2024-12-26\", name=\"Christmas\"},\
+                                {date=\"2024-12-30@2h, 2024-12-31:pm\", name=\"New Year's Eve\"}]\n",
+        )
+        .unwrap();
+        cal.recurring_holidays.push((
+            YearlyDate {
+                first: DateObj::Date(date("2020-11-11")),
+                interval: 1,
+                count: None,
+                until: None,
+            },
+            "Independence Day".to_string(),
+        ));
+        assert_eq!(cal.day_info(&date("2024-12-23")), DayInfo::WorkingDay(8));
+        assert_eq!(
+            cal.day_info(&date("2024-12-25")),
+            DayInfo::NonWorkingPubHoliday
+        );
+        assert_eq!(cal.day_info(&date("2024-12-27")), DayInfo::WorkingDay(6));
+        assert_eq!(cal.day_info(&date("2024-12-19")), DayInfo::NonWorking);
+        assert_eq!(cal.day_info(&date("2024-12-30")), DayInfo::PartialDay(6));
+        assert_eq!(cal.day_info(&date("2024-12-31")), DayInfo::PartialDay(4));
+        assert_eq!(
+            cal.public_holidays[1].date[1].to_string(),
+            "2024-12-31:pm".to_string()
+        );
         assert_eq!(
-            cal.public_holidays[0].date[0],
-            DateObj::Date(NaiveDate::parse_from_str("2024-01-01", DATE_FMT).unwrap())
+            cal.day_info(&date("2024-11-11")),
+            DayInfo::NonWorkingPubHoliday
         );
+        assert_eq!(cal.day_info(&date("2019-11-11")), DayInfo::WorkingDay(8));
+    }
+
+    #[test]
+    fn test_no_working_hours() {
+        assert!(BusinessDaysCalendar::from("closed_days = []\nworking_hrs_in_day = 0\n").is_err());
+        assert!(BusinessDaysCalendar::from(
+            "closed_days = [\"saturday\", \"sunday\"]\n\
+             working_hrs_in_day = 8\n\
+             working_hrs_by_weekday = {monday=0, tuesday=0, wednesday=0, thursday=0, friday=0}\n",
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_year_covered() {
+        let cal = BusinessDaysCalendar::from(
+            "closed_days = [\"saturday\", \"sunday\"]\n\
+             working_hrs_in_day = 8\n\
+             public_holidays = [{date=\"2023-12-30:2024-01-02\", name=\"Break\"}]\n",
+        )
+        .unwrap();
+        assert!(!cal.year_covered(2022));
+        assert!(cal.year_covered(2023));
+        assert!(cal.year_covered(2024));
+        assert!(!cal.year_covered(2025));
+        let cal = BusinessDaysCalendar::from(include_str!("../../examples/calendar_pl.toml"));
+        assert!(cal.unwrap().year_covered(2030));
     }
 }
@@ -4,9 +4,16 @@ use serde::Deserialize;
 pub struct PlantUMLConfig {
     pub use_api: bool,
     pub api_url: String,
+    /// Timeout of requests to the PlantUML server in seconds
+    #[serde(default = "default_api_timeout")]
+    pub api_timeout: u64,
     pub local_cmd: String,
 }
 
+fn default_api_timeout() -> u64 {
+    30
+}
+
 #[derive(Debug, Deserialize)]
 pub struct Colors {
     pub worker_pub_holidays: String,
@@ -16,15 +23,58 @@ pub struct Colors {
     pub worker_underloaded: String,
     pub worker_fine: String,
     pub worker_unassigned: String,
+    #[serde(default = "default_worker_partial_day")]
+    pub worker_partial_day: String,
 
     pub time_markers: String,
+    #[serde(default = "default_critical_path")]
+    pub critical_path: String,
+    /// Task bars (backends drawing the chart on their own)
+    #[serde(default = "default_task")]
+    pub task: String,
+    /// Shifts of team rotations
+    #[serde(default = "default_rotation")]
+    pub rotation: String,
+    /// Summary bars of parent tasks
+    #[serde(default = "default_group")]
+    pub group: String,
+}
+
+fn default_worker_partial_day() -> String {
+    "khaki".into()
+}
+
+fn default_critical_path() -> String {
+    "red".into()
+}
+
+fn default_task() -> String {
+    "steelblue".into()
+}
+
+fn default_rotation() -> String {
+    "plum".into()
+}
+
+fn default_group() -> String {
+    "dimgray".into()
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct IcsConfig {
+    /// Add public holidays and personal absences to the calendars
+    pub include_absences: bool,
 }
 
 // Define a struct for backend settings, which contains plantuml configuration
 #[derive(Debug, Deserialize)]
 pub struct BackendConfig {
+    /// Output format (backend name), auto if not defined
+    pub format: Option<String>,
     pub plantuml: PlantUMLConfig,
     pub colors: Colors,
+    #[serde(default)]
+    pub ics: IcsConfig,
 }
 
 // Define the top-level configuration struct
@@ -50,6 +100,6 @@ mod test {
         let cfg = Config::from(include_str!("../../default.cfg.toml"));
         assert!(cfg.is_ok());
         let cfg = cfg.unwrap();
-        assert_eq!(cfg.backend.plantuml.use_api, true);
+        assert!(cfg.backend.plantuml.use_api);
     }
 }
@@ -1,7 +1,8 @@
 use crate::{calendar, cfg, project};
-use chrono::{Days, NaiveDate, Weekday};
-use std::cell::Cell;
-use std::collections::{BTreeMap, HashMap, VecDeque};
+use chrono::{Datelike, Days, NaiveDate, Weekday};
+use serde::Serialize;
+use std::cell::{Cell, RefCell};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 
 #[derive(Debug, Clone)]
 struct ProcessError(String);
@@ -14,19 +15,75 @@ impl std::fmt::Display for ProcessError {
 
 impl std::error::Error for ProcessError {}
 
-#[derive(Debug)]
+/// Days in a row without working hours after which a worker is
+/// considered to never work again
+const MAX_IDLE_DAYS: u64 = 366;
+
+#[derive(Debug, Serialize)]
 pub struct Task {
     pub id: String,
     pub name: String,
+    /// id of the parent group
+    pub parent: Option<String>,
     pub assignee: String,
+    /// everybody working on the task, `assignee` is the first of them
+    pub assignees: Vec<String>,
+    /// ids of the tasks this one depends on
     pub after: Vec<String>,
+    /// dependency types and lags, in the order of `after`
+    pub dependencies: Vec<project::Dependency>,
     pub start_on: NaiveDate,
     pub end_on: NaiveDate,
     pub pause_days: Vec<NaiveDate>,
     pub duration_hours: u32,
+    /// ideal estimate in days (PERT expected value for three-point estimates)
+    pub estimate: f64,
+    pub focus_factor: f64,
+    pub slack: Slack,
+    pub deadline: Option<NaiveDate>,
+    /// working days of the assignee between the deadline and `end_on`
+    pub days_late: u32,
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+/// Critical path analysis of a task, all values are expressed
+/// in (calendar) days since the project start.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Serialize)]
+pub struct Slack {
+    pub earliest_start: f64,
+    pub earliest_finish: f64,
+    pub latest_start: f64,
+    pub latest_finish: f64,
+    /// how much the task can slip without delaying the project
+    pub total_float: f64,
+    /// how much the task can slip without delaying any successor
+    pub free_float: f64,
+    pub critical: bool,
+}
+
+impl Slack {
+    /// Slack of a group spanning all of `subtasks`: the group can slip as
+    /// much as its most critical subtask
+    fn summary(subtasks: &[Slack]) -> Slack {
+        let min = |f: fn(&Slack) -> f64| subtasks.iter().map(f).fold(f64::INFINITY, f64::min);
+        let earliest_start = min(|s| s.earliest_start);
+        let earliest_finish = subtasks
+            .iter()
+            .map(|s| s.earliest_finish)
+            .fold(earliest_start, f64::max);
+        let total_float = min(|s| s.total_float);
+        Slack {
+            earliest_start,
+            earliest_finish,
+            latest_start: earliest_start + total_float,
+            latest_finish: earliest_finish + total_float,
+            total_float,
+            free_float: min(|s| s.free_float).min(total_float),
+            critical: total_float.abs() < 0.001,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize)]
 pub enum WorkerDay {
     PubHolidays,
     Holidays,
@@ -35,12 +92,14 @@ pub enum WorkerDay {
     Underloaded,
     Fine,
     Unassigned,
+    /// Part of the day is taken by holidays or other duties
+    PartialDay,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub struct Hours(pub f64);
 
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub struct ResourceAllocation(pub BTreeMap<String, BTreeMap<NaiveDate, (Hours, WorkerDay)>>);
 
 impl ResourceAllocation {
@@ -59,22 +118,128 @@ impl ResourceAllocation {
     }
 }
 
-#[derive(Debug)]
+/// Parent task summarizing its subtasks
+#[derive(Debug, Serialize)]
+pub struct Group {
+    pub id: String,
+    pub name: String,
+    /// id of the parent group
+    pub parent: Option<String>,
+    /// ids of the tasks this group (so all its subtasks) depends on
+    pub after: Vec<String>,
+    /// dependency types and lags, in the order of `after`
+    pub dependencies: Vec<project::Dependency>,
+    pub start_on: NaiveDate,
+    pub end_on: NaiveDate,
+    /// ideal estimate of all subtasks in days
+    pub estimate: f64,
+    pub slack: Slack,
+}
+
+/// Entry of the work breakdown structure
+#[derive(Debug, Clone, Copy)]
+pub enum OutlineItem<'a> {
+    Group(&'a Group),
+    Task(&'a Task),
+}
+
+/// Milestone with its forecast date
+#[derive(Debug, Serialize)]
+pub struct Milestone {
+    pub id: String,
+    pub name: String,
+    /// ids of the tasks and milestones this one depends on
+    pub after: Vec<String>,
+    /// dependency types and lags, in the order of `after`
+    pub dependencies: Vec<project::Dependency>,
+    /// forecast date
+    pub date: NaiveDate,
+    pub target: Option<NaiveDate>,
+    /// working days between the target and the forecast, negative when ahead
+    pub slip_days: i32,
+    pub slack: Slack,
+}
+
+/// Shift of a team rotation
+#[derive(Debug, Clone, Serialize)]
+pub struct Shift {
+    pub rotation: String,
+    pub member: String,
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+}
+
+#[derive(Debug, Serialize)]
 pub struct GanttData {
     pub title: String,
+    /// Tasks in the order of the work breakdown structure
     pub tasks: Vec<Task>,
+    /// Parent tasks in the order of the project
+    pub groups: Vec<Group>,
+    pub milestones: Vec<Milestone>,
     pub project_starts: NaiveDate,
     pub closed_days: Vec<Weekday>,
     /// <worker_name, [absences]>
-    pub workers_absence: HashMap<String, Vec<NaiveDate>>,
+    pub workers_absence: BTreeMap<String, Vec<NaiveDate>>,
     pub public_holidays: Vec<NaiveDate>,
     pub resource_allocation: ResourceAllocation,
     pub time_markers: Vec<project::TimeMarker>,
+    pub project_ends: NaiveDate,
+    /// Standard deviation of the project end date in days
+    /// (non-zero only if three-point estimates are used)
+    pub end_date_std_dev: f64,
+    /// Non-fatal issues found while scheduling
+    pub warnings: Vec<String>,
+    /// Shifts of team rotations during the project
+    pub rotations: Vec<Shift>,
+}
+
+impl GanttData {
+    /// Groups of the task (or of the group), the outermost one first
+    pub fn ancestors(&self, parent: Option<&String>) -> Vec<&Group> {
+        let mut ret = Vec::new();
+        let mut parent = parent;
+        while let Some(g) = parent.and_then(|p| self.groups.iter().find(|g| &g.id == p)) {
+            ret.insert(0, g);
+            parent = g.parent.as_ref();
+        }
+        ret
+    }
+
+    /// Groups and tasks in the order of the work breakdown structure with
+    /// their nesting depth, every group is followed by its subtasks
+    pub fn outline(&self) -> Vec<(usize, OutlineItem<'_>)> {
+        let mut ret = Vec::new();
+        let mut open: Vec<&Group> = Vec::new();
+        for t in &self.tasks {
+            let path = self.ancestors(t.parent.as_ref());
+            let common = open
+                .iter()
+                .zip(&path)
+                .take_while(|(a, b)| a.id == b.id)
+                .count();
+            for (depth, g) in path.iter().enumerate().skip(common) {
+                ret.push((depth, OutlineItem::Group(g)));
+            }
+            ret.push((path.len(), OutlineItem::Task(t)));
+            open = path;
+        }
+        ret
+    }
 }
 
 #[derive(Debug, Copy, Clone)]
 struct ProjTaskIndx(usize);
 
+/// What a node of the task graph stands for
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum NodeKind {
+    Task,
+    /// parent task spanning its subtasks
+    Group,
+    Milestone,
+}
+
 impl ProjTaskIndx {
     fn get<'a>(&self, tasks: &'a [project::Task]) -> Option<&'a project::Task> {
         if self.0 >= tasks.len() {
@@ -85,7 +250,7 @@ impl ProjTaskIndx {
     }
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 struct GraphNodeId(usize);
 
 #[derive(Debug)]
@@ -103,30 +268,227 @@ impl Graph {
         }
     }
 
-    fn calc_start_time(&self, n: &GraphNode) -> bool {
-        let mut cumulative_days = 0_f64;
-        for p in &n.parents {
+    /// Adds children of the node with all parents computed to `ready`
+    fn push_ready_children(&self, n: &GraphNode, ready: &mut Vec<GraphNodeId>) {
+        for ch in &n.children {
+            let child = self.get_node(*ch).unwrap();
+            if !ready.contains(ch) && self.parents_computed(child) {
+                ready.push(*ch);
+            }
+        }
+    }
+
+    /// True when all parents of the node are computed
+    fn parents_computed(&self, n: &GraphNode) -> bool {
+        n.parents
+            .iter()
+            .all(|p| self.get_node(*p).unwrap().cumulative_days.get().is_some())
+    }
+
+    /// Returns the earliest start and the earliest finish (in cumulative
+    /// days) allowed by the dependencies or None if any parent is not
+    /// computed yet. `shift` moves a point in time by a lag in working days,
+    /// the resulting lags in calendar days are kept for the critical path.
+    fn calc_start_time(
+        &self,
+        n: &GraphNode,
+        deps: &[project::Dependency],
+        shift: impl Fn(f64, f64) -> f64,
+    ) -> Option<(f64, f64)> {
+        let (mut start, mut finish) = (0_f64, 0_f64);
+        for ((p, dep), gap) in n.parents.iter().zip(deps).zip(&n.gaps) {
             let par = self.get_node(*p).unwrap();
-            if let Some(s) = par.cumulative_days.get() {
-                cumulative_days = cumulative_days.max(s);
-            } else {
-                return false;
+            let anchor = match dep.kind {
+                project::DependencyKind::FS | project::DependencyKind::FF => {
+                    par.cumulative_days.get()?
+                }
+                project::DependencyKind::SS | project::DependencyKind::SF => {
+                    par.start_days.get()?
+                }
+            };
+            let bound = shift(anchor, dep.lag);
+            gap.set(bound - anchor);
+            match dep.kind {
+                project::DependencyKind::FS | project::DependencyKind::SS => {
+                    start = start.max(bound)
+                }
+                project::DependencyKind::FF | project::DependencyKind::SF => {
+                    finish = finish.max(bound)
+                }
             }
         }
-        n.cumulative_days.set(Some(cumulative_days));
-        true
+        Some((start, finish))
+    }
+
+    /// Dependencies between nodes: task dependencies and, when resources
+    /// are leveled, the previous task of the same worker.
+    fn edges(&self) -> Vec<Edge> {
+        let mut edges = Vec::new();
+        for (i, n) in self.graph.iter().enumerate() {
+            for ((p, kind), gap) in n.parents.iter().zip(&n.kinds).zip(&n.gaps) {
+                edges.push(Edge {
+                    from: p.0,
+                    to: i,
+                    kind: *kind,
+                    gap: gap.get(),
+                });
+            }
+            for (r, gap) in n.resource_parents.borrow().iter() {
+                edges.push(Edge {
+                    from: r.0,
+                    to: i,
+                    kind: project::DependencyKind::FS,
+                    gap: *gap,
+                });
+            }
+        }
+        edges
+    }
+
+    /// Returns outgoing edges of every node and nodes in topological order.
+    fn topological_order(&self, edges: &[Edge]) -> (Vec<Vec<usize>>, Vec<usize>) {
+        let len = self.graph.len();
+        let mut successors = vec![Vec::new(); len];
+        let mut in_degree = vec![0; len];
+        for (i, e) in edges.iter().enumerate() {
+            successors[e.from].push(i);
+            in_degree[e.to] += 1;
+        }
+        let mut order: Vec<usize> = (0..len).filter(|i| in_degree[*i] == 0).collect();
+        let mut i = 0;
+        while i < order.len() {
+            for e in &successors[order[i]] {
+                let s = edges[*e].to;
+                in_degree[s] -= 1;
+                if in_degree[s] == 0 {
+                    order.push(s);
+                }
+            }
+            i += 1;
+        }
+        (successors, order)
+    }
+
+    /// Critical path analysis of the computed graph, the scheduler does
+    /// the forward pass so only the backward pass is done here.
+    /// Returns the analysis for every node (indexed as the graph).
+    fn critical_path(&self) -> Vec<Slack> {
+        let len = self.graph.len();
+        let edges = self.edges();
+        let (successors, order) = self.topological_order(&edges);
+        let mut ret = vec![Slack::default(); len];
+        // forward pass has been already done by the scheduler
+        for (n, s) in self.graph.iter().zip(ret.iter_mut()) {
+            s.earliest_start = n.start_days.get().unwrap_or(0.0);
+            s.earliest_finish = n.cumulative_days.get().unwrap_or(0.0);
+        }
+        let project_finish = ret.iter().map(|s| s.earliest_finish).fold(0.0, f64::max);
+        // backward pass
+        for i in order.iter().rev() {
+            let lf = successors[*i]
+                .iter()
+                .map(|e| edges[*e].latest_finish(&ret))
+                .fold(project_finish, f64::min);
+            let free_float = successors[*i]
+                .iter()
+                .map(|e| edges[*e].slack(&ret))
+                .fold(project_finish - ret[*i].earliest_finish, f64::min);
+            let s = &mut ret[*i];
+            s.latest_finish = lf;
+            s.latest_start = lf - (s.earliest_finish - s.earliest_start);
+            s.total_float = s.latest_start - s.earliest_start;
+            s.free_float = free_float;
+            s.critical = s.total_float.abs() < 0.001;
+        }
+        ret
+    }
+
+    /// Standard deviation (in days) of the project end date, computed
+    /// along the critical path with the highest variance.
+    /// `std_devs` are standard deviations of the task durations in days.
+    fn end_date_std_dev(&self, slacks: &[Slack], std_devs: &[f64]) -> f64 {
+        let edges = self.edges();
+        let (_, order) = self.topological_order(&edges);
+        let project_finish = slacks.iter().map(|s| s.earliest_finish).fold(0.0, f64::max);
+        let mut path_variance = vec![0.0; self.graph.len()];
+        let mut ret = 0_f64;
+        for i in order {
+            if !slacks[i].critical {
+                continue;
+            }
+            let prev = edges
+                .iter()
+                .filter(|e| e.to == i && slacks[e.from].critical)
+                .filter(|e| e.slack(slacks).abs() < 0.001)
+                .map(|e| path_variance[e.from])
+                .fold(0.0, f64::max);
+            path_variance[i] = prev + std_devs[i] * std_devs[i];
+            if (slacks[i].earliest_finish - project_finish).abs() < 0.001 {
+                ret = ret.max(path_variance[i]);
+            }
+        }
+        ret.sqrt()
+    }
+}
+
+/// Dependency between two graph nodes
+#[derive(Debug)]
+struct Edge {
+    from: usize,
+    to: usize,
+    kind: project::DependencyKind,
+    /// lag in calendar days
+    gap: f64,
+}
+
+impl Edge {
+    /// How much the successor could start (or finish) earlier without
+    /// breaking the dependency, 0 when the dependency drives it
+    fn slack(&self, slacks: &[Slack]) -> f64 {
+        let (p, n) = (&slacks[self.from], &slacks[self.to]);
+        let (anchor, bound) = match self.kind {
+            project::DependencyKind::FS => (p.earliest_finish, n.earliest_start),
+            project::DependencyKind::SS => (p.earliest_start, n.earliest_start),
+            project::DependencyKind::FF => (p.earliest_finish, n.earliest_finish),
+            project::DependencyKind::SF => (p.earliest_start, n.earliest_finish),
+        };
+        bound - anchor - self.gap
+    }
+
+    /// The latest finish of the predecessor allowed by the successor
+    fn latest_finish(&self, slacks: &[Slack]) -> f64 {
+        let (p, n) = (&slacks[self.from], &slacks[self.to]);
+        let duration = p.earliest_finish - p.earliest_start;
+        match self.kind {
+            project::DependencyKind::FS => n.latest_start - self.gap,
+            project::DependencyKind::SS => n.latest_start - self.gap + duration,
+            project::DependencyKind::FF => n.latest_finish - self.gap,
+            project::DependencyKind::SF => n.latest_finish - self.gap + duration,
+        }
     }
 }
 
 #[derive(Debug)]
 struct GraphNode {
     task_id: ProjTaskIndx,
+    /// cumulative days at which the task is finished
     cumulative_days: Cell<Option<f64>>,
+    /// cumulative days at which the task starts
+    start_days: Cell<Option<f64>>,
+    /// previous tasks of the same workers with the (negative) gap between
+    /// the end of the task and the moment the worker got free, the owner
+    /// of a smaller share of a shared task is free earlier (resource
+    /// leveling only)
+    resource_parents: RefCell<Vec<(GraphNodeId, f64)>>,
     parents: Vec<GraphNodeId>,
+    /// dependency types of `parents`
+    kinds: Vec<project::DependencyKind>,
+    /// lags of `parents` in calendar days, set when the start is calculated
+    gaps: Vec<Cell<f64>>,
     children: Vec<GraphNodeId>,
 }
 
-fn build_task_graph(tasks: &[project::Task]) -> Graph {
+fn build_task_graph(tasks: &[project::Task]) -> Result<Graph, Box<dyn std::error::Error>> {
     let mut lookup = HashMap::new();
     let mut starting_points = Vec::new();
     let mut graph = Vec::with_capacity(tasks.len());
@@ -143,7 +505,11 @@ fn build_task_graph(tasks: &[project::Task]) -> Graph {
         graph.push(GraphNode {
             task_id,
             cumulative_days,
+            start_days: Cell::new(None),
+            resource_parents: RefCell::new(Vec::new()),
             parents,
+            kinds: Vec::new(),
+            gaps: Vec::new(),
             children,
         });
     }
@@ -152,17 +518,27 @@ fn build_task_graph(tasks: &[project::Task]) -> Graph {
         let task = &tasks[graph[i].task_id.0];
         for after in &task.after {
             // update: parent's children
-            let parent_id = *lookup.get(&after).unwrap();
+            let parent_id = match lookup.get(&after.task) {
+                Some(id) => *id,
+                None => {
+                    return Err(report_err(format!(
+                        "Task '{}' depends on unknown task '{}'",
+                        task.id, after.task
+                    )))
+                }
+            };
             let parent_node = &mut graph[parent_id.0];
             parent_node.children.push(GraphNodeId(i));
             // update node parent
-            graph[i].parents.push(parent_id)
+            graph[i].parents.push(parent_id);
+            graph[i].kinds.push(after.kind);
+            graph[i].gaps.push(Cell::new(0.0));
         }
     }
-    Graph {
+    Ok(Graph {
         starting_points,
         graph,
-    }
+    })
 }
 
 fn get_day_info(
@@ -170,61 +546,349 @@ fn get_day_info(
     cal: &calendar::BusinessDaysCalendar,
     worker: &project::TeamMember,
 ) -> calendar::DayInfo {
-    let hrs = match cal.day_info(d) {
-        calendar::DayInfo::WorkingDay(h) => h,
+    match cal.day_info(d) {
+        calendar::DayInfo::WorkingDay(_) | calendar::DayInfo::PartialDay(_) => {}
         di => return di,
     };
-    if calendar::in_date_obj_vec(d, &worker.holidays) {
+    // personal working hours apply to days the calendar is open
+    let hrs = worker
+        .working_hrs_by_weekday
+        .get(&d.weekday())
+        .copied()
+        .or(worker.working_hrs_in_day)
+        .unwrap_or(cal.working_hrs(d.weekday()));
+    if hrs == 0 {
+        return calendar::DayInfo::NonWorking;
+    }
+    // recurring absences are handled as holidays or other duties entries
+    let (mut holidays, mut other_duties) = (Vec::new(), Vec::new());
+    for a in &worker.recurring_absences {
+        if let Some(date) = a.on(d) {
+            match a.kind {
+                project::AbsenceKind::Holidays => holidays.push(date),
+                project::AbsenceKind::OtherDuties => other_duties.push(date),
+            }
+        }
+    }
+    if calendar::in_date_obj_vec(d, &worker.holidays)
+        || calendar::in_date_obj_vec(d, &holidays)
+        || worker.recurring_holidays.iter().any(|y| y.contains(d))
+    {
         return calendar::DayInfo::WorkerHolidays;
     }
-    if calendar::in_date_obj_vec(d, &worker.other_duties) {
+    if calendar::in_date_obj_vec(d, &worker.other_duties)
+        || calendar::in_date_obj_vec(d, &other_duties)
+    {
         return calendar::DayInfo::WorkerOtherDuties;
     }
-    // todo: worker might have a custom settings about the
-    // length of a working day
-    calendar::DayInfo::WorkingDay(hrs)
+    let pub_holidays = cal.partial_holiday_hrs(d, hrs);
+    let holidays = calendar::partial_day_hrs(d, &worker.holidays, hrs)
+        + calendar::partial_day_hrs(d, &holidays, hrs);
+    let other_duties = calendar::partial_day_hrs(d, &worker.other_duties, hrs)
+        + calendar::partial_day_hrs(d, &other_duties, hrs);
+    match pub_holidays + holidays + other_duties {
+        0 => calendar::DayInfo::WorkingDay(hrs),
+        off if off < hrs => calendar::DayInfo::PartialDay(hrs - off),
+        // parts of the day add up to the whole day
+        _ if pub_holidays > 0 => calendar::DayInfo::NonWorkingPubHoliday,
+        _ if holidays > 0 => calendar::DayInfo::WorkerHolidays,
+        _ => calendar::DayInfo::WorkerOtherDuties,
+    }
+}
+
+/// How a worker spends a day of a task
+#[derive(Debug)]
+enum TaskDay {
+    /// the calendar is closed or the worker is absent
+    Off(calendar::DayInfo),
+    /// all working hours are taken by standing commitments
+    Committed,
+    /// hours spent on the task
+    Worked(WorkerDay, f64),
+}
+
+/// Finish of a task (in cumulative days) and the days spent on it
+type TaskRun = (f64, Vec<(NaiveDate, TaskDay)>);
+
+/// Works on a task from `start` (in cumulative days) until `hours_to_burn`
+/// are burned, returns the finish (in cumulative days) and the days spent.
+fn run_task(
+    project_begin: NaiveDate,
+    start: f64,
+    mut hours_to_burn: f64,
+    focus_factor: f64,
+    cal: &calendar::BusinessDaysCalendar,
+    worker: &project::TeamMember,
+) -> Result<TaskRun, Box<dyn std::error::Error>> {
+    let mut cumulative_days = start;
+    let mut days = Vec::new();
+    if hours_to_burn <= 0.0 {
+        // zero-length task, done as soon as it starts
+        return Ok((cumulative_days, days));
+    }
+    let start_on = project_begin + Days::new(cumulative_days as u64);
+    let mut idle_days = 0;
+    for d in start_on.iter_days() {
+        if idle_days > MAX_IDLE_DAYS {
+            return Err(report_err(format!(
+                "Worker '{}' has no working hours for a year since {}",
+                worker.name,
+                d - Days::new(idle_days)
+            )));
+        }
+        let working_hrs = match get_day_info(&d, cal, worker) {
+            calendar::DayInfo::WorkingDay(h) | calendar::DayInfo::PartialDay(h) => {
+                h as f64 - committed_hrs(worker, &d, h as f64)
+            }
+            day_info => {
+                days.push((d, TaskDay::Off(day_info)));
+                cumulative_days += 1.0;
+                idle_days += 1;
+                continue;
+            }
+        };
+        if working_hrs < 1e-9 {
+            // the whole day is taken by standing commitments
+            days.push((d, TaskDay::Committed));
+            cumulative_days += 1.0;
+            idle_days += 1;
+            continue;
+        }
+        idle_days = 0;
+        // calculate effective amount of hours
+        let mut effective_working_hrs = working_hrs * focus_factor;
+        // what if a previous task finished in this day?
+        // we need to adjust currently available hours
+        let left_day = cumulative_days % 1.0;
+        let mut cumulative_day_len = 1.0;
+        if left_day.abs() > 0.001 {
+            // prev task was finished in this day
+            let remaining_fraction = 1.0 - left_day;
+            effective_working_hrs *= remaining_fraction;
+            cumulative_day_len *= remaining_fraction;
+        }
+
+        if hours_to_burn >= effective_working_hrs {
+            // whole day will be assigned to this task
+            hours_to_burn -= effective_working_hrs;
+            cumulative_days += cumulative_day_len;
+            days.push((
+                d,
+                TaskDay::Worked(WorkerDay::Fine, working_hrs * cumulative_day_len),
+            ));
+            if hours_to_burn.abs() < 1e-10 {
+                break;
+            }
+        } else {
+            // only a fraction of this day will be assigned to the task
+            // (effectively this task is about to be finished)
+            assert!(effective_working_hrs != 0.0);
+            let fraction = hours_to_burn / effective_working_hrs;
+            assert!(fraction <= 1.0);
+            cumulative_days += cumulative_day_len * fraction;
+            days.push((
+                d,
+                TaskDay::Worked(
+                    WorkerDay::Underloaded,
+                    working_hrs * fraction * cumulative_day_len,
+                ),
+            ));
+            break;
+        }
+    }
+    Ok((cumulative_days, days))
 }
 
-fn get_working_day_len(
-    day_info: &calendar::DayInfo,
+fn record_day(
     d: NaiveDate,
-    worker_name: &String,
-    workers_absence: &mut HashMap<String, Vec<NaiveDate>>,
+    day: &TaskDay,
+    worker_name: &str,
+    workers_absence: &mut BTreeMap<String, Vec<NaiveDate>>,
     resource_allocation: &mut ResourceAllocation,
     pause_days: &mut Vec<NaiveDate>,
     public_holidays: &mut Vec<NaiveDate>,
-) -> Option<u32> {
-    match day_info {
-        calendar::DayInfo::WorkerHolidays | calendar::DayInfo::WorkerOtherDuties => {
+) {
+    match day {
+        TaskDay::Off(calendar::DayInfo::WorkerHolidays) => {
             workers_absence
-                .entry(worker_name.clone())
+                .entry(worker_name.to_owned())
                 .or_default()
                 .push(d);
-            if *day_info == calendar::DayInfo::WorkerHolidays {
-                resource_allocation.add(&worker_name, d, WorkerDay::Holidays, Hours(0.0));
-            } else {
-                resource_allocation.add(&worker_name, d, WorkerDay::OtherDuties, Hours(0.0));
-            }
+            resource_allocation.add(worker_name, d, WorkerDay::Holidays, Hours(0.0));
             pause_days.push(d);
-            None
         }
-        calendar::DayInfo::WorkingDay(h) => Some(*h),
-        calendar::DayInfo::NonWorkingPubHoliday => {
+        TaskDay::Off(calendar::DayInfo::WorkerOtherDuties) => {
+            workers_absence
+                .entry(worker_name.to_owned())
+                .or_default()
+                .push(d);
+            resource_allocation.add(worker_name, d, WorkerDay::OtherDuties, Hours(0.0));
+            pause_days.push(d);
+        }
+        TaskDay::Off(calendar::DayInfo::NonWorkingPubHoliday) => {
             public_holidays.push(d);
             workers_absence
-                .entry(worker_name.clone())
+                .entry(worker_name.to_owned())
                 .or_default()
                 .push(d);
-            resource_allocation.add(&worker_name, d, WorkerDay::PubHolidays, Hours(0.0));
+            resource_allocation.add(worker_name, d, WorkerDay::PubHolidays, Hours(0.0));
             pause_days.push(d);
-            None
         }
-        _ => {
+        TaskDay::Off(_) => {
             pause_days.push(d);
-            resource_allocation.add(&worker_name, d, WorkerDay::PubHolidays, Hours(0.0));
-            None
+            resource_allocation.add(worker_name, d, WorkerDay::PubHolidays, Hours(0.0));
+        }
+        TaskDay::Committed => {
+            resource_allocation.add(worker_name, d, WorkerDay::OtherDuties, Hours(0.0));
+            pause_days.push(d);
+        }
+        TaskDay::Worked(kind, h) => resource_allocation.add(worker_name, d, *kind, Hours(*h)),
+    }
+}
+
+fn is_working_day(
+    d: &NaiveDate,
+    cal: &calendar::BusinessDaysCalendar,
+    worker: &project::TeamMember,
+) -> bool {
+    matches!(
+        get_day_info(d, cal, worker),
+        calendar::DayInfo::WorkingDay(_) | calendar::DayInfo::PartialDay(_)
+    )
+}
+
+/// Number of working days after `from` up to `to` (inclusive)
+fn working_days_after(
+    from: NaiveDate,
+    to: NaiveDate,
+    is_working: impl Fn(&NaiveDate) -> bool,
+) -> u32 {
+    from.iter_days()
+        .skip(1)
+        .take_while(|d| *d <= to)
+        .filter(|d| is_working(d))
+        .count() as u32
+}
+
+/// Milestone as a zero-duration task of the task graph
+fn milestone_task(m: &project::Milestone) -> project::Task {
+    project::Task {
+        id: m.id.clone(),
+        name: m.name.clone(),
+        estimate: Some(0.0),
+        optimistic: None,
+        likely: None,
+        pessimistic: None,
+        uncertainty: None,
+        after: m.after.clone(),
+        priority: None,
+        not_before: None,
+        deadline: None,
+        fixed_start: None,
+        parent: None,
+    }
+}
+
+/// Moves `t` (in cumulative days) by `lag` working days of the worker,
+/// negative lags move it back.
+fn add_working_days(
+    project_begin: NaiveDate,
+    mut t: f64,
+    lag: f64,
+    is_working: impl Fn(&NaiveDate) -> bool,
+) -> f64 {
+    let working = |day: f64| is_working(&(project_begin + chrono::Duration::days(day as i64)));
+    let mut remaining = lag.abs();
+    let mut idle_days = 0;
+    // no working days ahead is reported when the task is run
+    while remaining > 1e-9 && idle_days <= MAX_IDLE_DAYS {
+        if lag > 0.0 {
+            let day = t.floor();
+            if working(day) {
+                let step = remaining.min(day + 1.0 - t);
+                remaining -= step;
+                t += step;
+                idle_days = 0;
+            } else {
+                t = day + 1.0;
+                idle_days += 1;
+            }
+        } else {
+            let day = t.ceil() - 1.0;
+            if working(day) {
+                let step = remaining.min(t - day);
+                remaining -= step;
+                t -= step;
+                idle_days = 0;
+            } else {
+                t = day;
+                idle_days += 1;
+            }
+        }
+    }
+    t
+}
+
+/// Hours of a working day taken by standing commitments of the worker
+fn committed_hrs(worker: &project::TeamMember, d: &NaiveDate, day_len: f64) -> f64 {
+    let hrs: f64 = worker
+        .commitments
+        .iter()
+        .map(|c| c.hours_on(d, day_len))
+        .sum();
+    hrs.min(day_len)
+}
+
+/// Person working on a task
+struct Owner<'a> {
+    assignment: &'a project::Assignment,
+    worker: &'a project::TeamMember,
+    /// part of the task effort (0 - 1.0)
+    share: f64,
+}
+
+impl Owner<'_> {
+    fn focus_factor(&self) -> f64 {
+        self.assignment
+            .focus_factor
+            .unwrap_or(self.worker.focus_factor)
+    }
+}
+
+/// Looks up the assignments and the assigned workers for every task,
+/// None for parent tasks.
+fn resolve_assignments(
+    proj: &project::ProjectConfig,
+) -> Result<Vec<Option<Vec<Owner<'_>>>>, Box<dyn std::error::Error>> {
+    let mut ret = Vec::with_capacity(proj.tasks.len());
+    for task in &proj.tasks {
+        if proj.is_parent(task) {
+            ret.push(None);
+            continue;
+        }
+        let shares = proj.shares(&task.id);
+        if shares.is_empty() {
+            return Err(report_err(format!("Task '{}' is not assigned", task.name)));
+        }
+        let mut owners = Vec::with_capacity(shares.len());
+        for (assignment, share) in shares {
+            let worker = if let Some(w) = proj.team.iter().find(|u| u.name == assignment.owner) {
+                w
+            } else {
+                return Err(report_err(format!(
+                    "Worker '{}' not defined",
+                    assignment.owner
+                )));
+            };
+            owners.push(Owner {
+                assignment,
+                worker,
+                share,
+            });
         }
+        ret.push(Some(owners));
     }
+    Ok(ret)
 }
 
 pub fn process(
@@ -232,183 +896,1132 @@ pub fn process(
     proj: &project::ProjectConfig,
     calendars: &HashMap<&String, calendar::BusinessDaysCalendar>,
 ) -> Result<GanttData, Box<dyn std::error::Error>> {
-    let graph = build_task_graph(&proj.tasks);
-    let mut task_queue = VecDeque::new();
-    task_queue.extend(&graph.starting_points);
+    // groups wait for their subtasks, milestones follow the tasks in the graph
+    let mut all_tasks = Vec::with_capacity(proj.tasks.len() + proj.milestones.len());
+    let mut kinds = Vec::with_capacity(all_tasks.capacity());
+    for t in &proj.tasks {
+        let mut task = t.clone();
+        if proj.is_parent(t) {
+            task.after = proj
+                .tasks
+                .iter()
+                .filter(|s| s.parent.as_ref() == Some(&t.id))
+                .map(|s| project::Dependency {
+                    task: s.id.clone(),
+                    kind: project::DependencyKind::FF,
+                    lag: 0.0,
+                })
+                .collect();
+            kinds.push(NodeKind::Group);
+        } else {
+            // dependencies of parents apply to all their subtasks
+            for p in proj.ancestors(t) {
+                task.after.extend(p.after.iter().cloned());
+            }
+            kinds.push(NodeKind::Task);
+        }
+        all_tasks.push(task);
+    }
+    for m in &proj.milestones {
+        all_tasks.push(milestone_task(m));
+        kinds.push(NodeKind::Milestone);
+    }
+    let graph = build_task_graph(&all_tasks)?;
+    let owners = resolve_assignments(proj)?;
+    // milestones and groups use the calendar of the first team member
+    let project_cal = proj
+        .team
+        .first()
+        .and_then(|m| calendars.get(&m.base_calendar));
+    if project_cal.is_none() && kinds.iter().any(|k| *k != NodeKind::Task) {
+        return Err(report_err(
+            "Milestones and task groups need at least one team member calendar".into(),
+        ));
+    }
+    let project_working_day = |d: &NaiveDate| {
+        project_cal.is_some_and(|cal| {
+            matches!(
+                cal.day_info(d),
+                calendar::DayInfo::WorkingDay(_) | calendar::DayInfo::PartialDay(_)
+            )
+        })
+    };
+    let resource_leveling = proj.resource_leveling.unwrap_or(true);
+    // tasks whose dependencies are already computed
+    let mut ready = graph.starting_points.clone();
+    // <worker_name, (cumulative days at which the worker becomes free, last task)>
+    let mut workers_free = HashMap::<&str, (f64, GraphNodeId)>::new();
     let mut tasks = Vec::new();
-    let mut workers_absence = HashMap::<String, Vec<NaiveDate>>::new();
+    let mut workers_absence = BTreeMap::<String, Vec<NaiveDate>>::new();
     let mut public_holidays = Vec::new();
     let mut resource_allocation = ResourceAllocation::new();
+    let mut warnings = Vec::new();
     let project_begin = proj.start_date;
     let mut project_end = project_begin;
-    // println!("Graph: {graph:?}");
-    while let Some(graph_node_id) = task_queue.pop_front() {
+    let days_since_begin = |d: NaiveDate| (d - project_begin).num_days() as f64;
+    while !ready.is_empty() {
+        // pick the task that can start first; if several tasks could start
+        // at the same time (e.g. they wait for the same worker) the one
+        // with the highest priority wins, then the one defined first
+        let mut best: Option<(usize, f64)> = None;
+        for (i, node_id) in ready.iter().enumerate() {
+            let node = graph.get_node(*node_id).unwrap();
+            let task = node.task_id.get(&all_tasks).unwrap();
+            if kinds[node.task_id.0] != NodeKind::Task {
+                // groups and milestones don't need anybody's time
+                best = Some((i, 0.0));
+                break;
+            }
+            let task_owners = owners[node.task_id.0].as_ref().unwrap();
+            let lead = task_owners[0].worker;
+            let cal = calendars.get(&lead.base_calendar).unwrap();
+            let (mut start, _) = graph
+                .calc_start_time(node, &task.after, |t, lag| {
+                    add_working_days(project_begin, t, lag, |d| is_working_day(d, cal, lead))
+                })
+                .unwrap();
+            start = start.max(task.not_before.map_or(0.0, days_since_begin));
+            if resource_leveling {
+                for o in task_owners {
+                    if let Some((free, _)) = workers_free.get(o.assignment.owner.as_str()) {
+                        start = start.max(*free);
+                    }
+                }
+            }
+            if let Some(fixed) = task.fixed_start {
+                start = days_since_begin(fixed);
+            }
+            let better = match best {
+                None => true,
+                Some((b, best_start)) => {
+                    if (start - best_start).abs() > 1e-9 {
+                        start < best_start
+                    } else {
+                        let best_task = graph.get_node(ready[b]).unwrap().task_id;
+                        let prio = task.priority.unwrap_or(0);
+                        let best_prio = best_task.get(&all_tasks).unwrap().priority.unwrap_or(0);
+                        prio > best_prio || (prio == best_prio && node.task_id.0 < best_task.0)
+                    }
+                }
+            };
+            if better {
+                best = Some((i, start));
+            }
+        }
+        let (best, _) = best.unwrap();
+        let graph_node_id = ready.swap_remove(best);
         let graph_node = graph.get_node(graph_node_id).unwrap();
-        let task = graph_node.task_id.get(&proj.tasks).unwrap();
-        let id = task.id.clone();
-        let name = task.name.clone();
-        // println!("Processing: {name}, after: {:?}", task.after);
-        if graph_node.cumulative_days.get().is_some() {
+        let task = graph_node.task_id.get(&all_tasks).unwrap();
+        if kinds[graph_node.task_id.0] != NodeKind::Task {
+            let (start, finish) = graph
+                .calc_start_time(graph_node, &task.after, |t, lag| {
+                    add_working_days(project_begin, t, lag, project_working_day)
+                })
+                .unwrap();
+            let start = match kinds[graph_node.task_id.0] {
+                // a group spans from the first to the last of its subtasks
+                NodeKind::Group => graph_node
+                    .parents
+                    .iter()
+                    .filter_map(|p| graph.get_node(*p).unwrap().start_days.get())
+                    .fold(finish, f64::min),
+                _ => start.max(finish),
+            };
+            graph_node.start_days.set(Some(start));
+            graph_node.cumulative_days.set(Some(start.max(finish)));
+            graph.push_ready_children(graph_node, &mut ready);
             continue;
         }
-        // ready for processing?
-        if !graph.calc_start_time(graph_node) {
-            // this node (task) can't be processed as one of its parent
-            // is not computed yet.
-            task_queue.push_back(graph_node_id);
-            continue;
-        } else {
-            // task_queue.extend(&graph_node.children);
-            // kind of a trick - place at the front just to go
-            // as far as possible with this path
-            for ch in &graph_node.children {
-                task_queue.push_front(*ch);
+        let id = task.id.clone();
+        let name = task.name.clone();
+        // let process this node (task)
+        let task_owners = owners[graph_node.task_id.0].as_ref().unwrap();
+        // lags and the deadline are measured in working days of the first owner
+        let worker = task_owners[0].worker;
+        let worker_cal = calendars.get(&worker.base_calendar).unwrap();
+        let (deps_start, finish_bound) = graph
+            .calc_start_time(graph_node, &task.after, |t, lag| {
+                add_working_days(project_begin, t, lag, |d| {
+                    is_working_day(d, worker_cal, worker)
+                })
+            })
+            .unwrap();
+        let mut start = deps_start.max(task.not_before.map_or(0.0, days_since_begin));
+        let fixed_start = task.fixed_start.map(days_since_begin);
+        if resource_leveling {
+            // the task waits for all its owners
+            for o in task_owners {
+                if let Some((free, last)) = workers_free.get(o.assignment.owner.as_str()) {
+                    start = start.max(*free);
+                    // a task with a fixed start may overlap with the previous one
+                    if fixed_start.is_none_or(|f| f >= *free - 1e-9) {
+                        let last_finish = graph.get_node(*last).unwrap().cumulative_days.get();
+                        let gap = *free - last_finish.unwrap_or(*free);
+                        graph_node.resource_parents.borrow_mut().push((*last, gap));
+                    }
+                }
             }
         }
-        // let process this node (task)
-        let mut cumulative_days = graph_node.cumulative_days.get().unwrap();
-        let assignment = if let Some(e) = proj.assignments.iter().find(|a| a.task == id) {
-            e
-        } else {
-            return Err(report_err(format!("Task '{name}' is not assigned")));
-        };
-        let worker_name = assignment.owner.clone();
-        let worker = if let Some(w) = proj.team.iter().find(|u| u.name == assignment.owner) {
-            w
-        } else {
-            return Err(report_err(format!("Worker '{worker_name}' not defined")));
+        if let Some(f) = fixed_start {
+            start = f;
+        }
+        // every owner burns their share of the effort with their own calendar
+        // and focus factor, the task is done when all of them are
+        let run_owners = |start: f64| {
+            task_owners
+                .iter()
+                .map(|o| {
+                    let cal = calendars.get(&o.worker.base_calendar).unwrap();
+                    let hours_to_burn =
+                        task.expected_estimate() * o.share * cal.working_hrs_in_day as f64;
+                    run_task(
+                        project_begin,
+                        start,
+                        hours_to_burn,
+                        o.focus_factor(),
+                        cal,
+                        o.worker,
+                    )
+                })
+                .collect::<Result<Vec<_>, _>>()
         };
-        let worker_cal = calendars.get(&worker.base_calendar).unwrap();
-        let after = task.after.clone();
-        let start_on = project_begin + Days::new(cumulative_days as u64);
+        let finish = |runs: &[TaskRun]| runs.iter().map(|r| r.0).fold(0.0, f64::max);
+        let mut runs = run_owners(start)?;
+        // finish-to-finish and start-to-finish dependencies delay the start
+        // until the task finishes late enough
+        while fixed_start.is_none() && finish(&runs) < finish_bound - 1e-9 {
+            start += finish_bound - finish(&runs);
+            runs = run_owners(start)?;
+        }
+        let cumulative_days = finish(&runs);
+        graph_node.start_days.set(Some(start));
+        let start_on = project_begin + Days::new(start as u64);
+        let end_on =
+            (project_begin + Days::new((cumulative_days.ceil() as u64).max(1) - 1)).max(start_on);
         let mut pause_days = Vec::new();
-        // calculate task length based on real calendar and focus factor
-        // TODO: replace the hardcoded day length with proper value defined in the cal
-        let mut hours_to_burn = task.estimate * 8.0;
-        let mut end_on = start_on;
-        // println!("Task: {name}");
-        for d in start_on.iter_days() {
-            let day_info = get_day_info(&d, worker_cal, worker);
-            let working_hrs = if let Some(h) = get_working_day_len(
-                &day_info,
-                d,
-                &worker_name,
-                &mut workers_absence,
-                &mut resource_allocation,
-                &mut pause_days,
-                &mut public_holidays,
-            ) {
-                h
-            } else {
-                cumulative_days += 1.0;
-                continue;
-            };
-            // calculate effective amount of hours
-            let focus_factor = match assignment.focus_factor {
-                Some(f) => f,
-                None => worker.focus_factor,
-            };
-            let mut effective_working_hrs = working_hrs as f64 * focus_factor;
-            // what if a previous task finished in this day?
-            // we need to adjust currently available hours
-            let left_day = cumulative_days % 1.0;
-            let mut cumulative_day_len = 1.0;
-            if left_day.abs() > 0.001 {
-                // prev task was finished in this day
-                let remaining_fraction = 1.0 - left_day;
-                effective_working_hrs *= remaining_fraction;
-                cumulative_day_len *= remaining_fraction;
-            }
-            let mut task_ends = false;
-
-            // println!("{name} => {hours_to_burn} (cum: {cumulative_days})");
-            if hours_to_burn >= effective_working_hrs {
-                // whole day will be assigned to this task
-                hours_to_burn -= effective_working_hrs;
-                cumulative_days += cumulative_day_len;
-                resource_allocation.add(
-                    &worker_name,
-                    d,
-                    WorkerDay::Fine,
-                    Hours(8.0 * cumulative_day_len),
+        let mut worked = BTreeSet::new();
+        for (o, (_, days)) in task_owners.iter().zip(&runs) {
+            for (d, day) in days {
+                record_day(
+                    *d,
+                    day,
+                    &o.assignment.owner,
+                    &mut workers_absence,
+                    &mut resource_allocation,
+                    &mut pause_days,
+                    &mut public_holidays,
                 );
-                if hours_to_burn.abs() < 1e-10 {
-                    task_ends = true;
+                if let TaskDay::Worked(..) = day {
+                    worked.insert(*d);
                 }
-            } else {
-                // only a fraction of this day will be assigned to the task
-                // (effectively this task is about to be finished)
-                assert!(effective_working_hrs != 0.0);
-                let fraction = hours_to_burn / effective_working_hrs;
-                assert!(fraction <= 1.0);
-                cumulative_days += cumulative_day_len * fraction;
-                resource_allocation.add(
-                    &worker_name,
-                    d,
-                    WorkerDay::Underloaded,
-                    Hours(8.0 * fraction * cumulative_day_len),
-                );
-                task_ends = true;
             }
-            if task_ends {
-                end_on = project_begin + Days::new(cumulative_days.ceil() as u64 - 1);
-                // println!("end on: {end_on}");
-                break;
+        }
+        // a shared task is paused only when nobody works on it
+        if task_owners.len() > 1 {
+            pause_days.retain(|d| !worked.contains(d));
+            pause_days.sort();
+            pause_days.dedup();
+        }
+        if let Some(fixed) = task.fixed_start {
+            if start < deps_start - 1e-9 || cumulative_days < finish_bound - 1e-9 {
+                warnings.push(format!(
+                    "Task '{}' is fixed to start on {fixed} before its dependencies allow",
+                    task.name
+                ));
+            }
+        }
+        let mut days_late = 0;
+        if let Some(deadline) = task.deadline {
+            days_late =
+                working_days_after(deadline, end_on, |d| is_working_day(d, worker_cal, worker));
+            if end_on > deadline {
+                let unit = if days_late == 1 { "day" } else { "days" };
+                warnings.push(format!(
+                    "Task '{}' misses its deadline {deadline} by {days_late} working {unit}",
+                    task.name
+                ));
             }
         }
         if end_on > project_end {
             project_end = end_on;
         }
-        let duration_hours = (24.0 * task.estimate) as u32;
+        let duration_hours = (24.0 * task.expected_estimate()) as u32;
         tasks.push(Task {
             id,
             name,
-            assignee: worker_name,
-            after,
+            parent: task.parent.clone(),
+            assignee: task_owners[0].assignment.owner.clone(),
+            assignees: task_owners
+                .iter()
+                .map(|o| o.assignment.owner.clone())
+                .collect(),
+            // own dependencies, without the ones of the parents
+            after: proj.tasks[graph_node.task_id.0]
+                .after
+                .iter()
+                .map(|d| d.task.clone())
+                .collect(),
+            dependencies: proj.tasks[graph_node.task_id.0].after.clone(),
             start_on,
             end_on,
             duration_hours,
+            estimate: task.expected_estimate(),
+            focus_factor: task_owners[0].focus_factor(),
             pause_days,
+            slack: Slack::default(),
+            deadline: task.deadline,
+            days_late,
         });
         // we have to update new cumulative_days
         graph_node.cumulative_days.set(Some(cumulative_days));
+        // owners are free again when done with their share
+        for (o, (finish, _)) in task_owners.iter().zip(&runs) {
+            let free = workers_free.get(o.assignment.owner.as_str());
+            if free.is_none_or(|(f, _)| *finish >= *f) {
+                workers_free.insert(o.assignment.owner.as_str(), (*finish, graph_node_id));
+            }
+        }
+        // children with all dependencies computed are ready now
+        graph.push_ready_children(graph_node, &mut ready);
+    }
+    if let Some(n) = graph.graph.iter().find(|n| n.cumulative_days.get().is_none()) {
+        let task = n.task_id.get(&all_tasks).unwrap();
+        return Err(report_err(format!(
+            "Task '{}' can't be scheduled, check for circular dependencies",
+            task.name
+        )));
+    }
+    // keep the order of tasks as defined in the project, subtasks
+    // follow their parents
+    let position: HashMap<&String, usize> = proj
+        .tasks
+        .iter()
+        .enumerate()
+        .map(|(i, t)| (&t.id, i))
+        .collect();
+    tasks.sort_by_key(|t| {
+        let task = &proj.tasks[position[&t.id]];
+        let mut key: Vec<usize> = proj
+            .ancestors(task)
+            .iter()
+            .rev()
+            .map(|a| position[&a.id])
+            .collect();
+        key.push(position[&t.id]);
+        key
+    });
+    let slacks = graph.critical_path();
+    // task estimate deviations scaled to the real duration of tasks
+    let std_devs: Vec<f64> = all_tasks
+        .iter()
+        .zip(&slacks)
+        .map(|(t, s)| {
+            let expected = t.expected_estimate();
+            if expected > 0.0 {
+                t.estimate_std_dev() * (s.earliest_finish - s.earliest_start) / expected
+            } else {
+                0.0
+            }
+        })
+        .collect();
+    let end_date_std_dev = graph.end_date_std_dev(&slacks, &std_devs);
+    for task in tasks.iter_mut() {
+        task.slack = slacks[position[&task.id]];
+    }
+    let mut groups = Vec::new();
+    for (i, t) in proj.tasks.iter().enumerate() {
+        if kinds[i] != NodeKind::Group {
+            continue;
+        }
+        let node = &graph.graph[i];
+        let (start, finish) = (
+            node.start_days.get().unwrap(),
+            node.cumulative_days.get().unwrap(),
+        );
+        let subtasks: Vec<usize> = (0..proj.tasks.len())
+            .filter(|s| {
+                kinds[*s] == NodeKind::Task
+                    && proj.ancestors(&proj.tasks[*s]).iter().any(|a| a.id == t.id)
+            })
+            .collect();
+        let estimate = subtasks
+            .iter()
+            .map(|s| proj.tasks[*s].expected_estimate())
+            .sum();
+        let subtask_slacks: Vec<Slack> = subtasks.iter().map(|s| slacks[*s]).collect();
+        let slack = Slack::summary(&subtask_slacks);
+        groups.push(Group {
+            id: t.id.clone(),
+            name: t.name.clone(),
+            parent: t.parent.clone(),
+            after: t.after.iter().map(|d| d.task.clone()).collect(),
+            dependencies: t.after.clone(),
+            start_on: project_begin + Days::new(start as u64),
+            end_on: project_begin + Days::new((finish.ceil() as u64).max(1) - 1),
+            estimate,
+            slack,
+        });
+    }
+    let mut milestones = Vec::new();
+    for (m, (n, slack)) in proj
+        .milestones
+        .iter()
+        .zip(graph.graph.iter().zip(&slacks).skip(proj.tasks.len()))
+    {
+        // a milestone is reached at the end of the day its dependencies finish
+        let days = n.cumulative_days.get().unwrap().ceil().max(1.0) as u64;
+        let date = project_begin + Days::new(days - 1);
+        project_end = project_end.max(date);
+        let slip_days = match m.target {
+            Some(t) if date > t => working_days_after(t, date, project_working_day) as i32,
+            Some(t) => -(working_days_after(date, t, project_working_day) as i32),
+            None => 0,
+        };
+        if slip_days > 0 {
+            let unit = if slip_days == 1 { "day" } else { "days" };
+            warnings.push(format!(
+                "Milestone '{}' slips {slip_days} working {unit} past its target {}",
+                m.name,
+                m.target.unwrap()
+            ));
+        }
+        milestones.push(Milestone {
+            id: m.id.clone(),
+            name: m.name.clone(),
+            after: m.after.iter().map(|d| d.task.clone()).collect(),
+            dependencies: m.after.clone(),
+            date,
+            target: m.target,
+            slip_days,
+            slack: *slack,
+        });
     }
     // fill resource allocation unassigned
-    for (_, days) in resource_allocation.0.iter_mut() {
+    for (worker, days) in resource_allocation.0.iter_mut() {
+        let member = proj.team.iter().find(|m| m.name == *worker).unwrap();
+        let cal = calendars.get(&member.base_calendar).unwrap();
         for d in project_begin.iter_days() {
             if d > project_end {
                 break;
             }
-            let d = &mut days.entry(d).or_insert((Hours(0.0), WorkerDay::Unassigned));
+            let day = &mut days.entry(d).or_insert((Hours(0.0), WorkerDay::Unassigned));
 
-            let h = d.0 .0;
+            let (capacity, partial) = match get_day_info(&d, cal, member) {
+                calendar::DayInfo::WorkingDay(hrs) => (hrs as f64, false),
+                calendar::DayInfo::PartialDay(hrs) => (hrs as f64, true),
+                _ => (0.0, false),
+            };
+            let task_hrs = day.0 .0;
+            let h = task_hrs + committed_hrs(member, &d, capacity);
+            day.0 .0 = h;
+            if task_hrs < 0.001 {
+                continue;
+            }
 
-            if h >= 8.001 {
-                d.1 = WorkerDay::Overloaded;
-                println!("Overloaded: {h}");
-            } else if h <= 7.999 && h >= 0.001 {
-                d.1 = WorkerDay::Underloaded;
-            } else if h > 7.999 && h < 8.001 {
-                d.1 = WorkerDay::Fine;
+            if h >= capacity + 0.001 {
+                day.1 = WorkerDay::Overloaded;
+                warnings.push(format!("Worker '{worker}' is overloaded on {d}: {h:.1}h"));
+            } else if partial {
+                day.1 = WorkerDay::PartialDay;
+            } else if h <= capacity - 0.001 {
+                day.1 = WorkerDay::Underloaded;
+            } else {
+                day.1 = WorkerDay::Fine;
             }
         }
     }
 
+    warnings.extend(calendar_coverage(calendars, project_begin, project_end));
+
+    let mut rotations = Vec::new();
+    for r in &proj.rotations {
+        for (member, from, to) in r.shifts(project_begin, project_end) {
+            rotations.push(Shift {
+                rotation: r.name.clone(),
+                member: member.to_string(),
+                from,
+                to,
+            });
+        }
+    }
+
     let project_starts = proj.start_date;
     let closed_days = calendars.values().next().unwrap().closed_days.clone();
     let time_markers = proj.time_markers.clone().unwrap_or_default();
     Ok(GanttData {
         title: proj.project_name.clone(),
         tasks,
+        groups,
+        milestones,
         project_starts,
         closed_days,
         workers_absence,
         public_holidays,
         resource_allocation,
         time_markers,
+        project_ends: project_end,
+        end_date_std_dev,
+        warnings,
+        rotations,
     })
 }
 
+/// Calendars which don't define public holidays for some years between `from` and `to`
+pub fn calendar_coverage(
+    calendars: &HashMap<&String, calendar::BusinessDaysCalendar>,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Vec<String> {
+    let mut names: Vec<&&String> = calendars.keys().collect();
+    names.sort();
+    let mut ret = Vec::new();
+    for name in names {
+        let uncovered: Vec<String> = (from.year()..=to.year())
+            .filter(|y| !calendars[*name].year_covered(*y))
+            .map(|y| y.to_string())
+            .collect();
+        if !uncovered.is_empty() {
+            ret.push(format!(
+                "Calendar '{name}' doesn't define public holidays for {}",
+                uncovered.join(", ")
+            ));
+        }
+    }
+    ret
+}
+
 fn report_err(msg: String) -> Box<ProcessError> {
     Box::new(ProcessError(msg))
 }
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::sync::LazyLock;
+
+    static EXAMPLE_CALENDAR: LazyLock<String> = LazyLock::new(|| "calendar_pl.toml".to_string());
+
+    pub(crate) fn default_cfg() -> cfg::Config {
+        cfg::Config::from(include_str!("../../default.cfg.toml")).unwrap()
+    }
+
+    /// Calendars used by the example projects
+    pub(crate) fn example_calendars() -> HashMap<&'static String, calendar::BusinessDaysCalendar> {
+        let cal = include_str!("../../examples/calendar_pl.toml");
+        HashMap::from([(
+            &*EXAMPLE_CALENDAR,
+            calendar::BusinessDaysCalendar::from(cal).unwrap(),
+        )])
+    }
+
+    /// Schedule of a project from the examples directory
+    pub(crate) fn run_example(name: &str) -> GanttData {
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("../examples")
+            .join(name);
+        let proj = project::ProjectConfig::from(&std::fs::read_to_string(path).unwrap()).unwrap();
+        process(&default_cfg(), &proj, &example_calendars()).unwrap()
+    }
+
+    const PROJ: &str = r#"
+project_name = "Leveling"
+start_date = "2024-10-01"
+team = [
+    { name="Ala", base_calendar="cal.toml", focus_factor=1.0, holidays="", other_duties=""},
+]
+tasks = [
+    {id="A", name="Task A", estimate=2},
+    {id="B", name="Task B", estimate=3, priority=1},
+]
+assignments = [
+    {task="A", owner="Ala"},
+    {task="B", owner="Ala"},
+]
+"#;
+
+    fn run(proj: &str) -> GanttData {
+        run_with_calendar(proj, include_str!("../../examples/calendar_pl.toml"))
+    }
+
+    fn run_with_calendar(proj: &str, cal: &str) -> GanttData {
+        let cfg = default_cfg();
+        let proj = project::ProjectConfig::from(proj).unwrap();
+        let cal_name = "cal.toml".to_string();
+        let mut calendars = HashMap::new();
+        calendars.insert(
+            &cal_name,
+            calendar::BusinessDaysCalendar::from(cal).unwrap(),
+        );
+        process(&cfg, &proj, &calendars).unwrap()
+    }
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn test_resource_leveling() {
+        let gantt = run(PROJ);
+        // B has higher priority so it goes first, A waits for it
+        assert_eq!(gantt.tasks[1].start_on, date("2024-10-01"));
+        assert_eq!(gantt.tasks[1].end_on, date("2024-10-03"));
+        assert_eq!(gantt.tasks[0].start_on, date("2024-10-04"));
+        assert_eq!(gantt.tasks[0].end_on, date("2024-10-07"));
+        assert!(gantt.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_resource_leveling_disabled() {
+        let gantt = run(&format!("resource_leveling = false\n{PROJ}"));
+        assert_eq!(gantt.tasks[0].start_on, date("2024-10-01"));
+        assert_eq!(gantt.tasks[1].start_on, date("2024-10-01"));
+        let days = &gantt.resource_allocation.0["Ala"];
+        assert_eq!(days[&date("2024-10-01")].1, WorkerDay::Overloaded);
+        assert!(!gantt.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_critical_path() {
+        let gantt = run(r#"
+project_name = "Critical"
+start_date = "2024-10-01"
+team = [
+    { name="Ala", base_calendar="cal.toml", focus_factor=1.0, holidays="", other_duties=""},
+    { name="Ola", base_calendar="cal.toml", focus_factor=1.0, holidays="", other_duties=""},
+]
+tasks = [
+    {id="A", name="Task A", estimate=2},
+    {id="B", name="Task B", estimate=1},
+    {id="C", name="Task C", estimate=1, after="A,B"},
+]
+assignments = [
+    {task="A", owner="Ala"},
+    {task="B", owner="Ola"},
+    {task="C", owner="Ola"},
+]
+"#);
+        let critical: Vec<_> = gantt.tasks.iter().map(|t| t.slack.critical).collect();
+        assert_eq!(critical, vec![true, false, true]);
+        let b = &gantt.tasks[1].slack;
+        assert!((b.total_float - 1.0).abs() < 1e-9);
+        assert!((b.free_float - 1.0).abs() < 1e-9);
+        assert!((b.latest_finish - 2.0).abs() < 1e-9);
+        assert_eq!(gantt.end_date_std_dev, 0.0);
+    }
+
+    #[test]
+    fn test_end_date_std_dev() {
+        let gantt = run(r#"
+project_name = "PERT"
+start_date = "2024-10-01"
+team = [
+    { name="Ala", base_calendar="cal.toml", focus_factor=1.0, holidays="", other_duties=""},
+]
+tasks = [
+    {id="A", name="Task A", optimistic=1, likely=1, pessimistic=1},
+    {id="B", name="Task B", optimistic=1, likely=2, pessimistic=9, after="A"},
+]
+assignments = [
+    {task="A", owner="Ala"},
+    {task="B", owner="Ala"},
+]
+"#);
+        // B takes 3 working days (Wed-Fri), no weekends in between
+        assert_eq!(gantt.tasks[1].end_on, date("2024-10-04"));
+        assert!((gantt.end_date_std_dev - 8.0 / 6.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_zero_estimate() {
+        let gantt = run(r#"
+project_name = "Zero"
+start_date = "2024-10-01"
+team = [
+    { name="Ala", base_calendar="cal.toml", focus_factor=1.0, holidays="", other_duties=""},
+]
+tasks = [
+    {id="A", name="Task A", estimate=0},
+    {id="B", name="Task B", estimate=1, after="A"},
+    {id="C", name="Task C", optimistic=0, likely=0, pessimistic=0, after="B"},
+]
+assignments = [
+    {task="A", owner="Ala"},
+    {task="B", owner="Ala"},
+    {task="C", owner="Ala"},
+]
+"#);
+        let dates: Vec<_> = gantt.tasks.iter().map(|t| (t.start_on, t.end_on)).collect();
+        assert_eq!(
+            dates,
+            vec![
+                (date("2024-10-01"), date("2024-10-01")),
+                (date("2024-10-01"), date("2024-10-01")),
+                (date("2024-10-02"), date("2024-10-02")),
+            ]
+        );
+        assert!(gantt.tasks.iter().all(|t| t.pause_days.is_empty()));
+    }
+
+    #[test]
+    fn test_calendar_coverage() {
+        let gantt = run_with_calendar(
+            &PROJ.replace("2024-10-01", "2024-12-30"),
+            "closed_days = [\"saturday\", \"sunday\"]\n\
+             working_hrs_in_day = 8\n\
+             public_holidays = [{date=\"2024-12-25\", name=\"Christmas Day\"}]\n",
+        );
+        assert_eq!(
+            gantt.warnings,
+            vec!["Calendar 'cal.toml' doesn't define public holidays for 2025"]
+        );
+        assert!(run(PROJ).warnings.is_empty());
+    }
+
+    #[test]
+    fn test_working_hours() {
+        let gantt = run_with_calendar(
+            r#"
+project_name = "Hours"
+start_date = "2024-10-01"
+team = [
+    { name="Ala", base_calendar="cal.toml", focus_factor=1.0, holidays="", other_duties=""},
+    { name="Ola", base_calendar="cal.toml", focus_factor=1.0, holidays="", other_duties="", working_hrs_in_day=4, working_hrs_by_weekday={wednesday=0}},
+]
+tasks = [
+    {id="A", name="Task A", estimate=4},
+    {id="B", name="Task B", estimate=1},
+]
+assignments = [
+    {task="A", owner="Ala"},
+    {task="B", owner="Ola"},
+]
+"#,
+            "closed_days = [\"saturday\", \"sunday\"]\n\
+             working_hrs_in_day = 8\n\
+             working_hrs_by_weekday = {friday=4}\n\
+             public_holidays = [{date=\"2024-12-25\", name=\"Christmas Day\"}]\n",
+        );
+        // 3 x 8h + 4h on Friday + 4h on Monday
+        assert_eq!(gantt.tasks[0].end_on, date("2024-10-07"));
+        // Tuesday and Thursday, Ola doesn't work on Wednesdays
+        assert_eq!(gantt.tasks[1].end_on, date("2024-10-03"));
+        let ala = &gantt.resource_allocation.0["Ala"];
+        assert_eq!(ala[&date("2024-10-04")].0 .0, 4.0);
+        assert_eq!(ala[&date("2024-10-04")].1, WorkerDay::Fine);
+        assert_eq!(ala[&date("2024-10-07")].1, WorkerDay::Underloaded);
+        let ola = &gantt.resource_allocation.0["Ola"];
+        assert_eq!(ola[&date("2024-10-01")].0 .0, 4.0);
+        assert_eq!(ola[&date("2024-10-01")].1, WorkerDay::Fine);
+        assert!(gantt.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_no_working_hours() {
+        // Ala works only on weekends, when the calendar is closed
+        let proj = project::ProjectConfig::from(
+            r#"
+project_name = "Hours"
+start_date = "2024-10-01"
+team = [
+    { name="Ala", base_calendar="calendar_pl.toml", focus_factor=1.0, holidays="", other_duties="", working_hrs_by_weekday={monday=0, tuesday=0, wednesday=0, thursday=0, friday=0}},
+]
+tasks = [
+    {id="A", name="Task A", estimate=1},
+    {id="B", name="Task B", estimate=1, after="A:FS+1d"},
+]
+assignments = [
+    {task="A", owner="Ala"},
+    {task="B", owner="Ala"},
+]
+"#,
+        )
+        .unwrap();
+        let err = process(&default_cfg(), &proj, &example_calendars()).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Processor error: Worker 'Ala' has no working hours for a year since 2024-10-01"
+        );
+    }
+
+    #[test]
+    fn test_commitments_without_capacity() {
+        // 8h a day of the calendar are taken by the commitment
+        let proj = project::ProjectConfig::from(
+            r#"
+project_name = "Commitments"
+start_date = "2024-10-01"
+team = [
+    { name="Ala", base_calendar="calendar_pl.toml", focus_factor=1.0, holidays="", other_duties="", commitments=[{name="Support", hours=8}]},
+]
+tasks = [{id="A", name="Task A", estimate=1}]
+assignments = [{task="A", owner="Ala"}]
+"#,
+        )
+        .unwrap();
+        let err = process(&default_cfg(), &proj, &example_calendars()).unwrap_err();
+        assert!(err.to_string().contains("no working hours for a year"));
+    }
+
+    #[test]
+    fn test_partial_days() {
+        let gantt = run_with_calendar(
+            r#"
+project_name = "Partial"
+start_date = "2024-10-01"
+team = [
+    { name="Ala", base_calendar="cal.toml", focus_factor=1.0, holidays="2024-10-01@4h", other_duties=""},
+    { name="Ola", base_calendar="cal.toml", focus_factor=1.0, holidays="2024-10-01:am", other_duties="2024-10-01:pm"},
+]
+tasks = [
+    {id="A", name="Task A", estimate=2},
+    {id="B", name="Task B", estimate=1},
+]
+assignments = [
+    {task="A", owner="Ala"},
+    {task="B", owner="Ola"},
+]
+"#,
+            "closed_days = [\"saturday\", \"sunday\"]\n\
+             working_hrs_in_day = 8\n\
+             public_holidays = [{date=\"2024-10-02:am\", name=\"Half day\"}]\n",
+        );
+        // 4h on Tuesday and Wednesday, 8h on Thursday
+        assert_eq!(gantt.tasks[0].end_on, date("2024-10-03"));
+        let ala = &gantt.resource_allocation.0["Ala"];
+        assert_eq!(ala[&date("2024-10-01")].0 .0, 4.0);
+        assert_eq!(ala[&date("2024-10-01")].1, WorkerDay::PartialDay);
+        assert_eq!(ala[&date("2024-10-02")].1, WorkerDay::PartialDay);
+        assert_eq!(ala[&date("2024-10-03")].1, WorkerDay::Fine);
+        // halves of the day add up to the whole day off
+        assert_eq!(gantt.tasks[1].start_on, date("2024-10-01"));
+        assert_eq!(gantt.tasks[1].pause_days, vec![date("2024-10-01")]);
+        assert_eq!(gantt.tasks[1].end_on, date("2024-10-03"));
+    }
+
+    #[test]
+    fn test_commitments() {
+        let gantt = run(r#"
+project_name = "Commitments"
+start_date = "2024-10-01"
+team = [
+    { name="Ala", base_calendar="cal.toml", focus_factor=1.0, holidays="", other_duties="", commitments=[{name="Support", percent=25}]},
+    { name="Ola", base_calendar="cal.toml", focus_factor=1.0, holidays="", other_duties="", commitments=[{name="Planning", hours=2, weekdays=["monday"], from="2024-10-07"}]},
+]
+tasks = [
+    {id="A", name="Task A", estimate=3},
+    {id="B", name="Task B", estimate=5},
+]
+assignments = [
+    {task="A", owner="Ala"},
+    {task="B", owner="Ola"},
+]
+"#);
+        // 6h a day left for the task
+        assert_eq!(gantt.tasks[0].end_on, date("2024-10-04"));
+        let ala = &gantt.resource_allocation.0["Ala"];
+        assert_eq!(ala[&date("2024-10-01")].0 .0, 8.0);
+        assert_eq!(ala[&date("2024-10-01")].1, WorkerDay::Fine);
+        assert_eq!(ala[&date("2024-10-07")].0 .0, 2.0);
+        assert_eq!(ala[&date("2024-10-07")].1, WorkerDay::Unassigned);
+        // 4 x 8h, 6h on Monday, 2h on Tuesday
+        assert_eq!(gantt.tasks[1].end_on, date("2024-10-08"));
+        let ola = &gantt.resource_allocation.0["Ola"];
+        assert_eq!(ola[&date("2024-10-07")].0 .0, 8.0);
+        assert!(gantt.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_rotations() {
+        let gantt = run(r#"
+project_name = "Rotations"
+start_date = "2024-10-01"
+team = [
+    { name="Ala", base_calendar="cal.toml", focus_factor=1.0, holidays="", other_duties=""},
+    { name="Ola", base_calendar="cal.toml", focus_factor=1.0, holidays="", other_duties=""},
+]
+rotations = [{name="On-call", members=["Ala", "Ola"], from="2024-09-30"}]
+tasks = [
+    {id="A", name="Task A", estimate=3},
+    {id="B", name="Task B", estimate=2},
+]
+assignments = [
+    {task="A", owner="Ala"},
+    {task="B", owner="Ola"},
+]
+"#);
+        // Ala is on call in the first week, Ola in the second one
+        assert_eq!(gantt.tasks[0].start_on, date("2024-10-01"));
+        assert_eq!(gantt.tasks[0].end_on, date("2024-10-09"));
+        assert_eq!(gantt.tasks[1].end_on, date("2024-10-02"));
+        let shifts: Vec<_> = gantt
+            .rotations
+            .iter()
+            .map(|s| (s.member.as_str(), s.from, s.to))
+            .collect();
+        assert_eq!(
+            shifts,
+            vec![
+                ("Ala", date("2024-09-30"), date("2024-10-06")),
+                ("Ola", date("2024-10-07"), date("2024-10-13")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_dependency_types() {
+        let mut team = String::new();
+        for name in ["Ala", "Ola", "Ela", "Iza", "Ula"] {
+            team += &format!(
+                "{{ name=\"{name}\", base_calendar=\"cal.toml\", focus_factor=1.0, holidays=\"\", other_duties=\"\"}},\n"
+            );
+        }
+        let gantt = run(&format!(
+            r#"
+project_name = "Dependencies"
+start_date = "2024-10-01"
+team = [{team}]
+tasks = [
+    {{id="A", name="Task A", estimate=4}},
+    {{id="B", name="Task B", estimate=2, after="A:SS+1d"}},
+    {{id="C", name="Task C", estimate=1, after="A:FF+2d"}},
+    {{id="D", name="Task D", estimate=1, after="A:-2d"}},
+    {{id="E", name="Task E", estimate=1, after=[{{task="B", kind="SF", lag=3}}]}},
+]
+assignments = [
+    {{task="A", owner="Ala"}},
+    {{task="B", owner="Ola"}},
+    {{task="C", owner="Ela"}},
+    {{task="D", owner="Iza"}},
+    {{task="E", owner="Ula"}},
+]
+"#
+        ));
+        let spans: Vec<_> = gantt.tasks.iter().map(|t| (t.start_on, t.end_on)).collect();
+        assert_eq!(
+            spans,
+            vec![
+                (date("2024-10-01"), date("2024-10-04")),
+                // a day after A starts
+                (date("2024-10-02"), date("2024-10-03")),
+                // two working days after A finishes, the weekend doesn't count
+                (date("2024-10-08"), date("2024-10-08")),
+                // two days before A finishes
+                (date("2024-10-03"), date("2024-10-03")),
+                // finishes three days after B starts
+                (date("2024-10-04"), date("2024-10-04")),
+            ]
+        );
+        assert_eq!(gantt.tasks[2].after, vec!["A"]);
+        assert!(gantt.tasks[0].slack.critical);
+        assert!(gantt.tasks[2].slack.critical);
+        assert!(!gantt.tasks[1].slack.critical);
+    }
+
+    #[test]
+    fn test_date_constraints() {
+        let gantt = run(r#"
+project_name = "Constraints"
+start_date = "2024-10-01"
+team = [
+    { name="Ala", base_calendar="cal.toml", focus_factor=1.0, holidays="", other_duties=""},
+    { name="Ola", base_calendar="cal.toml", focus_factor=1.0, holidays="", other_duties=""},
+]
+tasks = [
+    {id="A", name="Task A", estimate=2},
+    {id="B", name="Task B", estimate=1, not_before="2024-10-10"},
+    {id="C", name="Task C", estimate=3, after="A", deadline="2024-10-04"},
+    {id="D", name="Task D", estimate=1, after="A", fixed_start="2024-10-01"},
+]
+assignments = [
+    {task="A", owner="Ala"},
+    {task="B", owner="Ala"},
+    {task="C", owner="Ola"},
+    {task="D", owner="Ola"},
+]
+"#);
+        assert_eq!(gantt.tasks[1].start_on, date("2024-10-10"));
+        assert_eq!(gantt.tasks[3].start_on, date("2024-10-01"));
+        // the weekend doesn't count
+        assert_eq!(gantt.tasks[2].end_on, date("2024-10-07"));
+        assert_eq!(gantt.tasks[2].days_late, 1);
+        assert_eq!(
+            gantt.warnings,
+            vec![
+                "Task 'Task D' is fixed to start on 2024-10-01 before its dependencies allow",
+                "Task 'Task C' misses its deadline 2024-10-04 by 1 working day",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_milestones() {
+        let gantt = run(r#"
+project_name = "Milestones"
+start_date = "2024-10-01"
+team = [
+    { name="Ala", base_calendar="cal.toml", focus_factor=1.0, holidays="", other_duties=""},
+    { name="Ola", base_calendar="cal.toml", focus_factor=1.0, holidays="", other_duties=""},
+]
+tasks = [
+    {id="A", name="Task A", estimate=2},
+    {id="B", name="Task B", estimate=3},
+    {id="C", name="Task C", estimate=1, after="M2"},
+]
+milestones = [
+    {id="M1", name="Kick-off", target="2024-10-03"},
+    {id="M2", name="Beta", after="A,B", target="2024-10-02"},
+]
+assignments = [
+    {task="A", owner="Ala"},
+    {task="B", owner="Ola"},
+    {task="C", owner="Ala"},
+]
+"#);
+        let [kick_off, beta] = &gantt.milestones[..] else {
+            panic!("2 milestones expected");
+        };
+        assert_eq!(kick_off.date, date("2024-10-01"));
+        assert_eq!(kick_off.slip_days, -2);
+        assert_eq!(beta.date, date("2024-10-03"));
+        assert_eq!(beta.slip_days, 1);
+        assert!(beta.slack.critical);
+        assert_eq!(gantt.tasks[2].start_on, date("2024-10-04"));
+        assert_eq!(
+            gantt.warnings,
+            vec!["Milestone 'Beta' slips 1 working day past its target 2024-10-02"]
+        );
+
+        let no_team = project::ProjectConfig::from(
+            r#"
+project_name = "Milestones"
+start_date = "2024-10-01"
+team = []
+tasks = []
+milestones = [{id="M1", name="Kick-off"}]
+assignments = []
+"#,
+        )
+        .unwrap();
+        assert!(process(&default_cfg(), &no_team, &HashMap::new()).is_err());
+    }
+
+    #[test]
+    fn test_groups() {
+        let gantt = run(r#"
+project_name = "Groups"
+start_date = "2024-10-01"
+team = [
+    { name="Ala", base_calendar="cal.toml", focus_factor=1.0, holidays="", other_duties=""},
+    { name="Ola", base_calendar="cal.toml", focus_factor=1.0, holidays="", other_duties=""},
+]
+tasks = [
+    {id="P1", name="Phase 1"},
+    {id="P2", name="Phase 2", after="P1"},
+    {id="A", name="Task A", estimate=2, parent="P1"},
+    {id="C", name="Task C", estimate=1, parent="P2"},
+    {id="B", name="Task B", estimate=3, parent="P1"},
+    {id="D", name="Task D", estimate=2, parent="P2", after="C"},
+    {id="E", name="Task E", estimate=1, after="P2"},
+]
+assignments = [
+    {task="A", owner="Ala"},
+    {task="B", owner="Ola"},
+    {task="C", owner="Ala"},
+    {task="D", owner="Ala"},
+    {task="E", owner="Ola"},
+]
+"#);
+        let ids: Vec<&str> = gantt.tasks.iter().map(|t| t.id.as_str()).collect();
+        assert_eq!(ids, vec!["A", "B", "C", "D", "E"]);
+        let [p1, p2] = &gantt.groups[..] else {
+            panic!("2 groups expected");
+        };
+        assert_eq!(
+            (p1.start_on, p1.end_on),
+            (date("2024-10-01"), date("2024-10-03"))
+        );
+        assert_eq!(p1.estimate, 5.0);
+        // subtasks of P2 wait for all of P1
+        assert_eq!(
+            (p2.start_on, p2.end_on),
+            (date("2024-10-04"), date("2024-10-08"))
+        );
+        assert_eq!(p2.estimate, 3.0);
+        assert!(p2.slack.critical);
+        let s = p1.slack;
+        assert_eq!(s.latest_start - s.earliest_start, s.total_float);
+        assert_eq!(s.latest_finish - s.earliest_finish, s.total_float);
+        assert!(gantt.tasks[2].after.is_empty());
+        assert_eq!(gantt.tasks[4].start_on, date("2024-10-09"));
+        let outline: Vec<(usize, &str)> = gantt
+            .outline()
+            .iter()
+            .map(|(depth, item)| match item {
+                OutlineItem::Group(g) => (*depth, g.id.as_str()),
+                OutlineItem::Task(t) => (*depth, t.id.as_str()),
+            })
+            .collect();
+        assert_eq!(
+            outline,
+            vec![
+                (0, "P1"),
+                (1, "A"),
+                (1, "B"),
+                (0, "P2"),
+                (1, "C"),
+                (1, "D"),
+                (0, "E")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_shared_tasks() {
+        let gantt = run(r#"
+project_name = "Shared"
+start_date = "2024-10-01"
+team = [
+    { name="Ala", base_calendar="cal.toml", focus_factor=1.0, holidays="", other_duties=""},
+    { name="Ola", base_calendar="cal.toml", focus_factor=1.0, holidays="2024-10-02", other_duties=""},
+]
+tasks = [
+    {id="A", name="Split", estimate=4},
+    {id="B", name="Pair work", estimate=2, after="A"},
+    {id="C", name="Uneven split", estimate=4, after="B"},
+]
+assignments = [
+    {task="A", owner="Ala"},
+    {task="A", owner="Ola"},
+    {task="B", owner="Ala", share=100},
+    {task="B", owner="Ola", share=100},
+    {task="C", owner="Ala", share=75},
+    {task="C", owner="Ola"},
+]
+"#);
+        let [a, b, c] = &gantt.tasks[..] else {
+            panic!("3 tasks expected");
+        };
+        assert_eq!(a.assignees, ["Ala", "Ola"]);
+        // Ola is off on Wednesday, but Ala works on the task
+        assert_eq!(
+            (a.start_on, a.end_on),
+            (date("2024-10-01"), date("2024-10-03"))
+        );
+        assert!(a.pause_days.is_empty());
+        // both work on the whole estimate
+        assert_eq!(
+            (b.start_on, b.end_on),
+            (date("2024-10-04"), date("2024-10-07"))
+        );
+        assert_eq!(
+            (c.start_on, c.end_on),
+            (date("2024-10-08"), date("2024-10-10"))
+        );
+        let ala = &gantt.resource_allocation.0["Ala"];
+        let ola = &gantt.resource_allocation.0["Ola"];
+        assert_eq!(ala[&date("2024-10-07")].0 .0, 8.0);
+        assert_eq!(ola[&date("2024-10-07")].0 .0, 8.0);
+        assert_eq!(ala[&date("2024-10-10")].0 .0, 8.0);
+        assert_eq!(ola[&date("2024-10-10")].1, WorkerDay::Unassigned);
+    }
+}
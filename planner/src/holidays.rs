@@ -0,0 +1,236 @@
+use chrono::{Datelike, Days, NaiveDate, Weekday};
+use serde::{self, Deserialize};
+use std::collections::BTreeSet;
+
+#[derive(Debug, Clone)]
+struct HolidayError(String);
+
+impl std::fmt::Display for HolidayError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "Holiday rule error: {}", self.0)
+    }
+}
+
+impl std::error::Error for HolidayError {}
+
+/// How a holiday falling on a weekend is observed
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Observed {
+    /// Saturday moves to Friday, Sunday to Monday
+    NearestWeekday,
+    /// moves to the next weekday which isn't a holiday already
+    NextWeekday,
+}
+
+/// Public holiday repeated every year, one of:
+/// - fixed date: `month` and `day`
+/// - `nth` `weekday` of `month`, negative `nth` counts from the end of the month
+/// - `easter` - offset in days from Easter Sunday
+#[derive(Debug, Clone, Deserialize)]
+pub struct HolidayRule {
+    pub name: String,
+    pub month: Option<u32>,
+    pub day: Option<u32>,
+    pub weekday: Option<Weekday>,
+    pub nth: Option<i32>,
+    pub easter: Option<i64>,
+    pub observed: Option<Observed>,
+    pub first_year: Option<i32>,
+    pub last_year: Option<i32>,
+}
+
+impl HolidayRule {
+    pub fn validate(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let valid = match (self.month, self.day, self.weekday, self.nth, self.easter) {
+            (Some(m), Some(d), None, None, None) => NaiveDate::from_ymd_opt(2024, m, d).is_some(),
+            (Some(m), None, Some(_), Some(n), None) => {
+                (1..=12).contains(&m) && (-5..=5).contains(&n) && n != 0
+            }
+            (None, None, None, None, Some(_)) => true,
+            _ => false,
+        };
+        if !valid {
+            return Err(report_err(format!(
+                "'{}' needs one of: month and day, month, weekday and nth (1..5 or -1..-5), easter offset",
+                self.name
+            )));
+        }
+        Ok(())
+    }
+
+    /// Date of the holiday in the given year, before applying `observed`
+    pub fn date_in(&self, year: i32) -> Option<NaiveDate> {
+        if self.first_year.is_some_and(|y| year < y) || self.last_year.is_some_and(|y| year > y) {
+            return None;
+        }
+        if let Some(offset) = self.easter {
+            let easter = easter_sunday(year)?;
+            return if offset < 0 {
+                easter.checked_sub_days(Days::new(offset.unsigned_abs()))
+            } else {
+                easter.checked_add_days(Days::new(offset as u64))
+            };
+        }
+        let month = self.month?;
+        if let Some(day) = self.day {
+            // e.g. 29th of February
+            return NaiveDate::from_ymd_opt(year, month, day);
+        }
+        nth_weekday_of_month(year, month, self.weekday?, self.nth?)
+    }
+}
+
+/// `nth` week day of a month, negative `nth` counts from the end of the month
+pub fn nth_weekday_of_month(
+    year: i32,
+    month: u32,
+    weekday: Weekday,
+    nth: i32,
+) -> Option<NaiveDate> {
+    if nth > 0 {
+        NaiveDate::from_weekday_of_month_opt(year, month, weekday, nth as u8)
+    } else {
+        let (y, m) = if month == 12 {
+            (year + 1, 1)
+        } else {
+            (year, month + 1)
+        };
+        let mut d = NaiveDate::from_ymd_opt(y, m, 1)?.pred_opt()?;
+        while d.weekday() != weekday {
+            d = d.pred_opt()?;
+        }
+        d.checked_sub_days(Days::new(7 * (nth.unsigned_abs() as u64 - 1)))
+            .filter(|d| d.month() == month)
+    }
+}
+
+fn is_weekend(d: &NaiveDate) -> bool {
+    matches!(d.weekday(), Weekday::Sat | Weekday::Sun)
+}
+
+/// Holidays generated by the rules for the given year, including
+/// days moved from weekends (which may fall into the previous year)
+pub fn expand(rules: &[HolidayRule], year: i32) -> Vec<(NaiveDate, &str)> {
+    let mut holidays: Vec<(NaiveDate, &HolidayRule)> = rules
+        .iter()
+        .filter_map(|r| r.date_in(year).map(|d| (d, r)))
+        .collect();
+    holidays.sort_by_key(|(d, _)| *d);
+    let mut taken: BTreeSet<NaiveDate> = holidays.iter().map(|(d, _)| *d).collect();
+    let mut ret = Vec::new();
+    for (d, rule) in holidays {
+        let observed = match rule.observed {
+            Some(Observed::NearestWeekday) if d.weekday() == Weekday::Sat => d.pred_opt(),
+            Some(Observed::NearestWeekday) if d.weekday() == Weekday::Sun => d.succ_opt(),
+            Some(Observed::NextWeekday) if is_weekend(&d) => {
+                let mut o = d;
+                while is_weekend(&o) || taken.contains(&o) {
+                    o = o.succ_opt().unwrap_or(o);
+                }
+                Some(o)
+            }
+            _ => Some(d),
+        };
+        if let Some(o) = observed {
+            taken.insert(o);
+            ret.push((o, rule.name.as_str()));
+        }
+    }
+    ret
+}
+
+/// Easter Sunday in the Gregorian calendar (anonymous Gregorian algorithm)
+pub fn easter_sunday(year: i32) -> Option<NaiveDate> {
+    let a = year % 19;
+    let b = year / 100;
+    let c = year % 100;
+    let d = b / 4;
+    let e = b % 4;
+    let f = (b + 8) / 25;
+    let g = (b - f + 1) / 3;
+    let h = (19 * a + b - d - g + 15) % 30;
+    let i = c / 4;
+    let k = c % 4;
+    let l = (32 + 2 * e + 2 * i - h - k) % 7;
+    let m = (a + 11 * h + 22 * l) / 451;
+    let month = (h + l - 7 * m + 114) / 31;
+    let day = (h + l - 7 * m + 114) % 31 + 1;
+    NaiveDate::from_ymd_opt(year, month as u32, day as u32)
+}
+
+#[derive(Deserialize)]
+struct RuleSet {
+    holiday_rules: Vec<HolidayRule>,
+}
+
+/// Built-in holiday rules of a country: PL, DE, UK (GB) or US
+pub fn builtin(country: &str) -> Result<Vec<HolidayRule>, Box<dyn std::error::Error>> {
+    let contents = match country.to_uppercase().as_str() {
+        "PL" => include_str!("../holidays/pl.toml"),
+        "DE" => include_str!("../holidays/de.toml"),
+        "UK" | "GB" => include_str!("../holidays/uk.toml"),
+        "US" => include_str!("../holidays/us.toml"),
+        _ => {
+            return Err(report_err(format!(
+                "no built-in holidays for '{country}', available: PL, DE, UK, US"
+            )))
+        }
+    };
+    let set: RuleSet = toml::from_str(contents)?;
+    Ok(set.holiday_rules)
+}
+
+fn report_err(msg: String) -> Box<HolidayError> {
+    Box::new(HolidayError(msg))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    fn dates(country: &str, year: i32) -> Vec<NaiveDate> {
+        let rules = builtin(country).unwrap();
+        expand(&rules, year).into_iter().map(|(d, _)| d).collect()
+    }
+
+    #[test]
+    fn test_easter_sunday() {
+        assert_eq!(easter_sunday(2024), Some(date("2024-03-31")));
+        assert_eq!(easter_sunday(2025), Some(date("2025-04-20")));
+        assert_eq!(easter_sunday(2038), Some(date("2038-04-25")));
+        assert_eq!(easter_sunday(2285), Some(date("2285-03-22")));
+    }
+
+    #[test]
+    fn test_builtin_rules() {
+        for country in ["pl", "de", "uk", "us"] {
+            for rule in builtin(country).unwrap() {
+                rule.validate().unwrap();
+            }
+        }
+        assert!(builtin("XX").is_err());
+
+        let us = dates("US", 2024);
+        // Thanksgiving, Memorial Day and observed Juneteenth (Sat 2027-06-19)
+        assert!(us.contains(&date("2024-11-28")));
+        assert!(us.contains(&date("2024-05-27")));
+        assert!(dates("US", 2027).contains(&date("2027-06-18")));
+        // New Year's Day 2022 is a Saturday, observed on the last day of 2021
+        assert!(dates("US", 2022).contains(&date("2021-12-31")));
+
+        // Christmas Day and Boxing Day on the weekend are moved to Monday and Tuesday
+        let uk = dates("UK", 2021);
+        assert!(uk.contains(&date("2021-12-27")));
+        assert!(uk.contains(&date("2021-12-28")));
+        assert!(uk.contains(&date("2021-08-30")));
+
+        let de = dates("DE", 2024);
+        assert_eq!(de.len(), 9);
+        assert!(de.contains(&date("2024-05-09")));
+    }
+}
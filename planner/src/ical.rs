@@ -0,0 +1,282 @@
+use crate::calendar::{DateObj, YearlyDate};
+use chrono::{Datelike, Days, NaiveDate, NaiveDateTime};
+
+#[derive(Debug, Clone)]
+struct IcsError(String);
+
+impl std::fmt::Display for IcsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "iCalendar error: {}", self.0)
+    }
+}
+
+impl std::error::Error for IcsError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IcsDate {
+    Once(DateObj),
+    Yearly(YearlyDate),
+}
+
+/// Whole-day event imported from an iCalendar file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IcsEvent {
+    pub summary: String,
+    pub date: IcsDate,
+}
+
+/// Date or date-time property value, e.g. `20241224` or `20241224T090000Z`
+#[derive(Debug, Copy, Clone)]
+enum Value {
+    Date(NaiveDate),
+    DateTime(NaiveDateTime),
+}
+
+impl Value {
+    fn parse(s: &str) -> Result<Value, Box<dyn std::error::Error>> {
+        let s = s.trim().trim_end_matches('Z');
+        if s.len() > 8 {
+            Ok(Value::DateTime(NaiveDateTime::parse_from_str(
+                s,
+                "%Y%m%dT%H%M%S",
+            )?))
+        } else {
+            Ok(Value::Date(NaiveDate::parse_from_str(s, "%Y%m%d")?))
+        }
+    }
+
+    fn date(&self) -> NaiveDate {
+        match self {
+            Value::Date(d) => *d,
+            Value::DateTime(dt) => dt.date(),
+        }
+    }
+}
+
+/// Content lines with folded lines joined
+fn unfold(contents: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in contents.lines() {
+        if let Some(cont) = line.strip_prefix([' ', '\t']) {
+            if let Some(last) = lines.last_mut() {
+                last.push_str(cont);
+                continue;
+            }
+        }
+        lines.push(line.to_string());
+    }
+    lines
+}
+
+fn unescape(s: &str) -> String {
+    s.replace("\\n", " ")
+        .replace("\\N", " ")
+        .replace("\\,", ",")
+        .replace("\\;", ";")
+        .replace("\\\\", "\\")
+}
+
+/// Days of `P<n>D` or `P<n>W` duration
+fn parse_duration_days(s: &str) -> Option<u64> {
+    let s = s.strip_prefix('P')?;
+    if let Some(w) = s.strip_suffix('W') {
+        return w.parse::<u64>().ok().map(|w| w * 7);
+    }
+    s.split('T').next()?.strip_suffix('D')?.parse().ok()
+}
+
+fn parse_yearly(
+    rule: &str,
+    first: DateObj,
+    summary: &str,
+) -> Result<YearlyDate, Box<dyn std::error::Error>> {
+    let (start, _) = first.span();
+    let mut yearly = YearlyDate {
+        first,
+        interval: 1,
+        count: None,
+        until: None,
+    };
+    for part in rule.split(';') {
+        match part.split_once('=') {
+            Some(("FREQ", "YEARLY")) => {}
+            Some(("FREQ", freq)) => {
+                return Err(Box::new(IcsError(format!(
+                    "event '{summary}' repeats {freq}, only yearly events are supported"
+                ))))
+            }
+            Some(("INTERVAL", n)) => yearly.interval = n.parse()?,
+            Some(("COUNT", n)) => yearly.count = Some(n.parse()?),
+            Some(("UNTIL", d)) => yearly.until = Some(Value::parse(d)?.date()),
+            Some(("WKST", _)) => {}
+            // accepted only when repeating the date of DTSTART
+            Some(("BYMONTH", m)) if m.parse() == Ok(start.month()) => {}
+            Some(("BYMONTHDAY", d)) if d.parse() == Ok(start.day()) => {}
+            _ => {
+                return Err(Box::new(IcsError(format!(
+                    "event '{summary}' uses unsupported recurrence rule part {part}"
+                ))))
+            }
+        }
+    }
+    Ok(yearly)
+}
+
+/// Extracts whole-day events: all-day events and timed events
+/// lasting at least 24 hours. Shorter timed events are skipped.
+pub fn parse(contents: &str) -> Result<Vec<IcsEvent>, Box<dyn std::error::Error>> {
+    let mut ret = Vec::new();
+    let mut event: Option<Vec<(String, String)>> = None;
+    for line in unfold(contents) {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        // drop parameters, e.g. DTSTART;VALUE=DATE
+        let name = name.split(';').next().unwrap_or_default().to_uppercase();
+        match (name.as_str(), value) {
+            ("BEGIN", "VEVENT") => event = Some(Vec::new()),
+            ("END", "VEVENT") => {
+                if let Some(props) = event.take() {
+                    if let Some(e) = convert_event(&props)? {
+                        ret.push(e);
+                    }
+                }
+            }
+            _ => {
+                if let Some(props) = event.as_mut() {
+                    props.push((name, value.to_string()));
+                }
+            }
+        }
+    }
+    Ok(ret)
+}
+
+fn convert_event(
+    props: &[(String, String)],
+) -> Result<Option<IcsEvent>, Box<dyn std::error::Error>> {
+    let get = |name: &str| {
+        props
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+    };
+    if get("STATUS") == Some("CANCELLED") {
+        return Ok(None);
+    }
+    let summary = unescape(get("SUMMARY").unwrap_or_default());
+    let Some(start) = get("DTSTART") else {
+        return Err(Box::new(IcsError(format!(
+            "event '{summary}' has no DTSTART"
+        ))));
+    };
+    let start = Value::parse(start)?;
+    let end = get("DTEND").map(Value::parse).transpose()?;
+    let duration = get("DURATION").and_then(parse_duration_days);
+    let (first, last) = match (start, end) {
+        // end date of all-day events is exclusive
+        (Value::Date(s), Some(e)) => (s, (e.date() - Days::new(1)).max(s)),
+        (Value::Date(s), None) => (s, s + Days::new(duration.unwrap_or(1).max(1) - 1)),
+        (Value::DateTime(s), Some(Value::DateTime(e))) if (e - s).num_hours() >= 24 => {
+            // event ending at midnight doesn't take the last day
+            let last = if e.time() == chrono::NaiveTime::MIN {
+                e.date() - Days::new(1)
+            } else {
+                e.date()
+            };
+            (s.date(), last)
+        }
+        (Value::DateTime(s), None) if duration.is_some_and(|d| d > 0) => {
+            (s.date(), s.date() + Days::new(duration.unwrap_or(1) - 1))
+        }
+        _ => return Ok(None),
+    };
+    let first = if first == last {
+        DateObj::Date(first)
+    } else {
+        DateObj::Range(first, last)
+    };
+    if get("EXDATE").is_some() || get("RDATE").is_some() {
+        return Err(Box::new(IcsError(format!(
+            "event '{summary}' has EXDATE or RDATE, which are not supported"
+        ))));
+    }
+    let date = match get("RRULE") {
+        Some(rule) => IcsDate::Yearly(parse_yearly(rule, first, &summary)?),
+        None => IcsDate::Once(first),
+    };
+    Ok(Some(IcsEvent { summary, date }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn test_parse_ics() {
+        let events = parse(
+            "BEGIN:VCALENDAR\r\n\
+             VERSION:2.0\r\n\
+             BEGIN:VEVENT\r\n\
+             DTSTART;VALUE=DATE:20241224\r\n\
+             SUMMARY:Christmas Eve\r\n\
+             END:VEVENT\r\n\
+             BEGIN:VEVENT\r\n\
+             DTSTART;VALUE=DATE:20240701\r\n\
+             DTEND;VALUE=DATE:20240713\r\n\
+             SUMMARY:Summer\r\n  vacation\r\n\
+             END:VEVENT\r\n\
+             BEGIN:VEVENT\r\n\
+             DTSTART;VALUE=DATE:20200101\r\n\
+             RRULE:FREQ=YEARLY;COUNT=10\r\n\
+             SUMMARY:New Year's Day\r\n\
+             END:VEVENT\r\n\
+             BEGIN:VEVENT\r\n\
+             DTSTART:20241010T090000Z\r\n\
+             DTEND:20241010T100000Z\r\n\
+             SUMMARY:Meeting\r\n\
+             END:VEVENT\r\n\
+             BEGIN:VEVENT\r\n\
+             DTSTART;TZID=Europe/Warsaw:20241014T000000\r\n\
+             DTEND;TZID=Europe/Warsaw:20241016T000000\r\n\
+             SUMMARY:Conference\r\n\
+             END:VEVENT\r\n\
+             END:VCALENDAR\r\n",
+        )
+        .unwrap();
+        assert_eq!(events.len(), 4);
+        assert_eq!(
+            events[0].date,
+            IcsDate::Once(DateObj::Date(date("2024-12-24")))
+        );
+        assert_eq!(events[1].summary, "Summer vacation");
+        assert_eq!(
+            events[1].date,
+            IcsDate::Once(DateObj::Range(date("2024-07-01"), date("2024-07-12")))
+        );
+        let IcsDate::Yearly(yearly) = &events[2].date else {
+            panic!("yearly event expected");
+        };
+        assert!(yearly.contains(&date("2025-01-01")));
+        assert!(!yearly.contains(&date("2025-01-02")));
+        assert!(!yearly.contains(&date("2030-01-01")));
+        assert_eq!(
+            events[3].date,
+            IcsDate::Once(DateObj::Range(date("2024-10-14"), date("2024-10-15")))
+        );
+
+        let weekly = "BEGIN:VEVENT\nDTSTART;VALUE=DATE:20241001\nRRULE:FREQ=WEEKLY\nEND:VEVENT\n";
+        assert!(parse(weekly).is_err());
+        let by_month = "BEGIN:VEVENT\nDTSTART;VALUE=DATE:20241225\nRRULE:FREQ=YEARLY;BYMONTH=12;BYMONTHDAY=25\nEND:VEVENT\n";
+        assert!(parse(by_month).is_ok());
+        // 4th Thursday of November
+        let by_day = "BEGIN:VEVENT\nDTSTART;VALUE=DATE:20241128\nRRULE:FREQ=YEARLY;BYMONTH=11;BYDAY=4TH\nEND:VEVENT\n";
+        assert!(parse(by_day).is_err());
+        let exdate = "BEGIN:VEVENT\nDTSTART;VALUE=DATE:20241224\nRRULE:FREQ=YEARLY\nEXDATE;VALUE=DATE:20251224\nEND:VEVENT\n";
+        assert!(parse(exdate).is_err());
+    }
+}
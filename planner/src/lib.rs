@@ -2,4 +2,14 @@ pub mod cfg;
 pub mod calendar;
 pub mod project;
 pub mod gantt_builder;
+pub mod holidays;
+pub mod ical;
+pub mod backend;
+pub mod backend_html;
+pub mod backend_ics;
+pub mod backend_json;
+pub mod backend_mermaid;
 pub mod backend_plantuml;
+pub mod backend_svg;
+pub mod backend_table;
+pub mod simulation;
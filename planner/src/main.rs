@@ -1,7 +1,8 @@
 use clap::Parser;
-use planner::{backend_plantuml, calendar, cfg, gantt_builder, project};
+use planner::{backend, calendar, cfg, gantt_builder, project, simulation};
 use std::collections::HashMap;
 use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
 use std::{env, fs};
 
 #[derive(Debug, Parser)]
@@ -15,6 +16,18 @@ struct Args {
     project_file: PathBuf,
     #[arg(short = 'c', long = "cfg", value_name = "CONFIG")]
     config_file: Option<PathBuf>,
+    /// Output format, overrides the one from the config
+    #[arg(short, long)]
+    format: Option<String>,
+    /// Run Monte Carlo simulation with the given number of iterations
+    #[arg(short, long, value_name = "ITERATIONS")]
+    simulate: Option<usize>,
+    /// Seed for the simulation, random if not given
+    #[arg(long)]
+    seed: Option<u64>,
+    /// Fail if a calendar doesn't define public holidays for every year of the project
+    #[arg(long)]
+    strict: bool,
 }
 
 fn do_the_calc(args: &Args) -> Result<(), Box<dyn std::error::Error>> {
@@ -23,28 +36,75 @@ fn do_the_calc(args: &Args) -> Result<(), Box<dyn std::error::Error>> {
     } else {
         cfg::Config::from(include_str!("../../default.cfg.toml"))?
     };
-    let proj = project::ProjectConfig::from(&fs::read_to_string(&args.project_file)?)?;
+    let mut proj = project::ProjectConfig::from(&fs::read_to_string(&args.project_file)?)?;
     let mut calendars = HashMap::new();
     let mut full_path = env::current_dir()?;
     if args.project_file.parent().is_some() {
         full_path.push(args.project_file.parent().unwrap());
     }
+    proj.load_ics(&full_path)?;
     for cal_file in proj.team.iter().map(|user| &user.base_calendar) {
+        if calendars.contains_key(cal_file) {
+            continue;
+        }
         let mut full_path = full_path.clone();
         full_path.push(cal_file);
-        calendars
-            .entry(cal_file)
-            .or_insert(calendar::BusinessDaysCalendar::from(&fs::read_to_string(
-                full_path,
-            )?)?);
-    }
-    backend_plantuml::build_chart(
-        &cfg,
-        &gantt_builder::process(&cfg, &proj, &calendars)?,
-        args.api_server,
-        &full_path,
-        &args.project_file.file_stem().unwrap().to_string_lossy()
-    )?;
+        let mut cal = calendar::BusinessDaysCalendar::from(&fs::read_to_string(&full_path)?)?;
+        cal.load_ics(full_path.parent().unwrap_or(&full_path))?;
+        calendars.insert(cal_file, cal);
+    }
+    let gantt = gantt_builder::process(&cfg, &proj, &calendars)?;
+    if args.strict {
+        let uncovered =
+            gantt_builder::calendar_coverage(&calendars, gantt.project_starts, gantt.project_ends);
+        if !uncovered.is_empty() {
+            return Err(uncovered.join("\n").into());
+        }
+    }
+    for w in &gantt.warnings {
+        eprintln!("Warning: {w}");
+    }
+    for m in &gantt.milestones {
+        match m.target {
+            Some(target) => println!(
+                "Milestone '{}' on {} (target: {target}, slip: {} working days)",
+                m.name, m.date, m.slip_days
+            ),
+            None => println!("Milestone '{}' on {}", m.name, m.date),
+        }
+    }
+    if gantt.end_date_std_dev > 0.0 {
+        println!(
+            "Project ends on {} (standard deviation: {:.1} days)",
+            gantt.project_ends, gantt.end_date_std_dev
+        );
+    }
+    let format = args
+        .format
+        .as_deref()
+        .or(cfg.backend.format.as_deref())
+        .unwrap_or("auto");
+    let use_api = backend::use_api(&cfg, args.api_server);
+    let format = backend::resolve_format(&cfg, format, use_api);
+    let proj_name = args.project_file.file_stem().unwrap().to_string_lossy();
+    let output = backend::Output {
+        dir: &full_path,
+        name: &proj_name,
+        use_api,
+    };
+    backend::Registry::default().render(format, &cfg, &gantt, &output)?;
+    if let Some(iterations) = args.simulate {
+        let seed = args.seed.unwrap_or_else(|| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_nanos() as u64)
+                .unwrap_or_default()
+        });
+        print!(
+            "{}",
+            simulation::simulate(&cfg, &proj, &calendars, iterations, seed)?
+        );
+    }
     Ok(())
 }
 
@@ -52,5 +112,6 @@ fn main() {
     let args = Args::parse();
     if let Err(e) = do_the_calc(&args) {
         eprintln!("Error: {e}");
+        std::process::exit(1);
     }
 }
@@ -1,26 +1,485 @@
-use crate::calendar::{parse_date_entry, parse_multidate_entry, DateObj};
-use chrono::NaiveDate;
+use crate::calendar::{
+    self, parse_date_entry, parse_multidate_entry, parse_opt_date_entry, DateObj, YearlyDate,
+};
+use crate::holidays;
+use crate::ical::IcsDate;
+use chrono::{Datelike, Days, NaiveDate, Weekday};
 use toml;
-use serde::{self, Deserialize};
+use serde::{self, Deserialize, Serialize};
+use std::collections::HashMap;
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone)]
+struct ProjectError(String);
+
+impl std::fmt::Display for ProjectError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "Project error: {}", self.0)
+    }
+}
+
+impl std::error::Error for ProjectError {}
+
+#[derive(Debug, Clone, Deserialize)]
 pub struct TeamMember {
     pub name: String,
     pub base_calendar: String,
     pub focus_factor: f64,
+    /// +/- percentage range of the focus factor used by the simulation
+    pub focus_factor_uncertainty: Option<f64>,
     #[serde(deserialize_with="parse_multidate_entry")]
     pub holidays: Vec<DateObj>,
     #[serde(deserialize_with="parse_multidate_entry")]
     pub other_duties: Vec<DateObj>,
+    /// iCalendar file with holidays (relative to the project file)
+    pub holidays_ics: Option<String>,
+    /// yearly holidays imported from `holidays_ics`
+    #[serde(skip)]
+    pub recurring_holidays: Vec<YearlyDate>,
+    /// overrides working hours of the calendar, e.g. for part-timers
+    pub working_hrs_in_day: Option<u32>,
+    /// overrides working hours of some week days, 0 for days off
+    #[serde(default)]
+    pub working_hrs_by_weekday: HashMap<Weekday, u32>,
+    /// standing commitments taking a part of every working day
+    #[serde(default)]
+    pub commitments: Vec<Commitment>,
+    /// absences repeated every week or month, e.g. on-call duties
+    #[serde(default)]
+    pub recurring_absences: Vec<RecurringAbsence>,
+}
+
+impl TeamMember {
+    fn validate(&self) -> Result<(), Box<dyn std::error::Error>> {
+        if self
+            .focus_factor_uncertainty
+            .is_some_and(|u| !(0.0..=100.0).contains(&u))
+        {
+            return Err(report_err(format!(
+                "Team member '{}' requires focus_factor_uncertainty between 0 and 100",
+                self.name
+            )));
+        }
+        // days without own hours fall back to the calendar
+        if calendar::WEEKDAYS.iter().all(|d| {
+            self.working_hrs_by_weekday.get(d).or(self.working_hrs_in_day.as_ref()) == Some(&0)
+        }) {
+            return Err(report_err(format!(
+                "Team member '{}' has no working hours on any week day",
+                self.name
+            )));
+        }
+        for c in &self.commitments {
+            c.validate(&self.name)?;
+        }
+        // commitments without an end taking the whole day, every day
+        let no_capacity = calendar::WEEKDAYS.iter().all(|d| {
+            let hrs = self.working_hrs_by_weekday.get(d).or(self.working_hrs_in_day.as_ref());
+            let (percent, hours) = self
+                .commitments
+                .iter()
+                .filter(|c| c.to.is_none() && (c.weekdays.is_empty() || c.weekdays.contains(d)))
+                .fold((0.0, 0.0), |(p, h), c| {
+                    (p + c.percent.unwrap_or(0.0), h + c.hours.unwrap_or(0.0))
+                });
+            percent >= 100.0
+                || hrs.is_some_and(|h| *h as f64 * (1.0 - percent / 100.0) <= hours)
+        });
+        if no_capacity {
+            return Err(report_err(format!(
+                "Team member '{}' has commitments taking all of their working hours",
+                self.name
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Part of working hours spent on other work, e.g. support or another project
+#[derive(Debug, Clone, Deserialize)]
+pub struct Commitment {
+    pub name: String,
+    /// percentage of the working hours of a day
+    pub percent: Option<f64>,
+    /// fixed number of hours a day
+    pub hours: Option<f64>,
+    /// first day of the commitment, from the beginning if not defined
+    #[serde(default, deserialize_with = "parse_opt_date_entry")]
+    pub from: Option<NaiveDate>,
+    /// last day of the commitment, forever if not defined
+    #[serde(default, deserialize_with = "parse_opt_date_entry")]
+    pub to: Option<NaiveDate>,
+    /// days of the week the commitment applies to, every day if empty
+    #[serde(default)]
+    pub weekdays: Vec<Weekday>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Repeat {
+    /// every `interval` weeks starting on `from`
+    Weekly,
+    /// every `interval` months on the given `day` or `nth` `weekday`
+    Monthly,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AbsenceKind {
+    Holidays,
+    #[default]
+    OtherDuties,
+}
+
+/// Absence repeated every week or month, handled like `holidays`
+/// or `other_duties` entries
+#[derive(Debug, Clone, Deserialize)]
+pub struct RecurringAbsence {
+    pub name: String,
+    pub repeat: Repeat,
+    /// number of weeks or months between occurrences, 1 if not defined
+    pub interval: Option<u32>,
+    /// first occurrence (weekly) or the first month (monthly)
+    #[serde(default, deserialize_with = "parse_opt_date_entry")]
+    pub from: Option<NaiveDate>,
+    /// no occurrences after this day
+    #[serde(default, deserialize_with = "parse_opt_date_entry")]
+    pub to: Option<NaiveDate>,
+    /// day of the month (monthly)
+    pub day: Option<u32>,
+    /// `nth` week day of the month (monthly), negative counts from the end
+    pub weekday: Option<Weekday>,
+    pub nth: Option<i32>,
+    /// length of an occurrence in days, 1 if not defined
+    pub days: Option<u32>,
+    /// part of the day: `am`, `pm` or hours like `2h`, whole day if not defined
+    pub part: Option<String>,
+    #[serde(default)]
+    pub kind: AbsenceKind,
+}
+
+impl RecurringAbsence {
+    /// Whole day or a part of the day `d` taken by the absence
+    pub fn on(&self, d: &NaiveDate) -> Option<DateObj> {
+        if self.from.is_some_and(|f| *d < f) || self.to.is_some_and(|t| *d > t) {
+            return None;
+        }
+        let interval = self.interval.unwrap_or(1).max(1) as i64;
+        let days = self.days.unwrap_or(1).max(1) as i64;
+        let occurs = match self.repeat {
+            Repeat::Weekly => {
+                let from = self.from?;
+                (*d - from).num_days() % (7 * interval) < days
+            }
+            Repeat::Monthly => {
+                let month = |d: NaiveDate| d.year() as i64 * 12 + d.month0() as i64;
+                let first = self.from.map(month).unwrap_or(0);
+                // an occurrence started in the previous month may last until now
+                [d.with_day(1)?, d.with_day(1)?.pred_opt()?]
+                    .into_iter()
+                    .filter(|m| (month(*m) - first).rem_euclid(interval) == 0)
+                    .filter_map(|m| self.start_in_month(m.year(), m.month()))
+                    .any(|start| start <= *d && (*d - start).num_days() < days)
+            }
+        };
+        if !occurs {
+            return None;
+        }
+        Some(match self.part.as_deref().and_then(calendar::DayPart::parse) {
+            Some(part) => DateObj::PartOfDay(*d, part),
+            None => DateObj::Date(*d),
+        })
+    }
+
+    fn start_in_month(&self, year: i32, month: u32) -> Option<NaiveDate> {
+        match (self.day, self.weekday, self.nth) {
+            (Some(day), _, _) => NaiveDate::from_ymd_opt(year, month, day),
+            (None, Some(weekday), Some(nth)) => holidays::nth_weekday_of_month(year, month, weekday, nth),
+            _ => None,
+        }
+    }
+
+    fn validate(&self, member: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let valid = match self.repeat {
+            Repeat::Weekly => self.from.is_some(),
+            Repeat::Monthly => match (self.day, self.weekday, self.nth) {
+                (Some(d), None, None) => (1..=31).contains(&d),
+                (None, Some(_), Some(n)) => n != 0 && (-5..=5).contains(&n),
+                _ => false,
+            },
+        };
+        if !valid {
+            return Err(report_err(format!(
+                "Recurring absence '{}' of '{member}' requires `from` (weekly), `day` or `weekday` and `nth` (monthly)",
+                self.name
+            )));
+        }
+        if self.part.as_deref().is_some_and(|p| calendar::DayPart::parse(p).is_none()) {
+            return Err(report_err(format!(
+                "Recurring absence '{}' of '{member}' has invalid part of the day",
+                self.name
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Duty rotating between team members, e.g. weekly on-call
+#[derive(Debug, Clone, Deserialize)]
+pub struct Rotation {
+    pub name: String,
+    /// team members in the order of the rotation
+    pub members: Vec<String>,
+    /// first day of the first shift
+    #[serde(deserialize_with = "parse_date_entry")]
+    pub from: NaiveDate,
+    /// no shifts after this day
+    #[serde(default, deserialize_with = "parse_opt_date_entry")]
+    pub to: Option<NaiveDate>,
+    /// length of a shift in weeks, 1 if not defined
+    pub weeks: Option<u32>,
+    /// part of the day taken by the duty, whole day if not defined
+    pub part: Option<String>,
+    #[serde(default)]
+    pub kind: AbsenceKind,
+}
+
+impl Rotation {
+    fn shift_days(&self) -> u32 {
+        7 * self.weeks.unwrap_or(1).max(1)
+    }
+
+    /// Shifts overlapping `from..=to`: (member, first day, last day)
+    pub fn shifts(&self, from: NaiveDate, to: NaiveDate) -> Vec<(&str, NaiveDate, NaiveDate)> {
+        let mut ret = Vec::new();
+        let shift = self.shift_days() as u64;
+        for (i, start) in self.from.iter_days().step_by(shift as usize).enumerate() {
+            if start > to || self.to.is_some_and(|t| start > t) {
+                break;
+            }
+            let mut end = start + Days::new(shift - 1);
+            if let Some(t) = self.to {
+                end = end.min(t);
+            }
+            if end >= from {
+                ret.push((self.members[i % self.members.len()].as_str(), start, end));
+            }
+        }
+        ret
+    }
+
+    /// The rotation as a recurring absence of every member
+    fn absences(&self) -> Vec<(&str, RecurringAbsence)> {
+        let shift = self.shift_days();
+        self.members
+            .iter()
+            .enumerate()
+            .map(|(i, member)| {
+                let absence = RecurringAbsence {
+                    name: self.name.clone(),
+                    repeat: Repeat::Weekly,
+                    interval: Some(shift / 7 * self.members.len() as u32),
+                    from: Some(self.from + Days::new((i as u32 * shift) as u64)),
+                    to: self.to,
+                    day: None,
+                    weekday: None,
+                    nth: None,
+                    days: Some(shift),
+                    part: self.part.clone(),
+                    kind: self.kind,
+                };
+                (member.as_str(), absence)
+            })
+            .collect()
+    }
+}
+
+impl Commitment {
+    /// Hours taken on the day `d` with `day_len` working hours
+    pub fn hours_on(&self, d: &NaiveDate, day_len: f64) -> f64 {
+        if self.from.is_some_and(|f| *d < f)
+            || self.to.is_some_and(|t| *d > t)
+            || (!self.weekdays.is_empty() && !self.weekdays.contains(&d.weekday()))
+        {
+            return 0.0;
+        }
+        let hours = match (self.percent, self.hours) {
+            (Some(p), _) => day_len * p / 100.0,
+            (_, Some(h)) => h,
+            _ => 0.0,
+        };
+        hours.min(day_len)
+    }
+
+    fn validate(&self, member: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let valid = match (self.percent, self.hours) {
+            (Some(p), None) => (0.0..=100.0).contains(&p),
+            (None, Some(h)) => h >= 0.0,
+            _ => false,
+        };
+        if !valid {
+            return Err(report_err(format!(
+                "Commitment '{}' of '{member}' requires either percent (0-100) or hours",
+                self.name
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Type of a dependency, which end of the predecessor constrains
+/// which end of the successor
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub enum DependencyKind {
+    /// finish-to-start
+    #[default]
+    FS,
+    /// start-to-start
+    SS,
+    /// finish-to-finish
+    FF,
+    /// start-to-finish
+    SF,
+}
+
+/// Dependency on another task, written as `T1`, `T1:SS`, `T1:FF+2d` or `T1:-1d`
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Dependency {
+    pub task: String,
+    #[serde(default)]
+    pub kind: DependencyKind,
+    /// Lag in working days of the successor, negative for leads
+    #[serde(default)]
+    pub lag: f64,
+}
+
+impl Dependency {
+    fn parse(s: &str) -> Result<Dependency, String> {
+        let (task, spec) = s.split_once(':').unwrap_or((s, ""));
+        let spec = spec.trim();
+        let split = spec.find(['+', '-']).unwrap_or(spec.len());
+        let kind = match spec[..split].trim().to_uppercase().as_str() {
+            "" | "FS" => DependencyKind::FS,
+            "SS" => DependencyKind::SS,
+            "FF" => DependencyKind::FF,
+            "SF" => DependencyKind::SF,
+            k => return Err(format!("unknown dependency type '{k}' in '{s}'")),
+        };
+        let lag = spec[split..].trim();
+        let lag = match lag.strip_suffix('d').unwrap_or(lag).replace(' ', "") {
+            l if l.is_empty() => 0.0,
+            l => l
+                .trim_start_matches('+')
+                .parse()
+                .map_err(|_| format!("invalid lag in '{s}'"))?,
+        };
+        Ok(Dependency {
+            task: task.trim().to_string(),
+            kind,
+            lag,
+        })
+    }
+}
+
+impl std::fmt::Display for Dependency {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.task)?;
+        if self.kind != DependencyKind::FS || self.lag != 0.0 {
+            write!(f, ":{:?}", self.kind)?;
+        }
+        if self.lag != 0.0 {
+            write!(f, "{:+}d", self.lag)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
 pub struct Task {
     pub id: String,
     pub name: String,
-    pub estimate: f64,
-    #[serde(default, deserialize_with="parse_vec_str")]
-    pub after: Vec<String>, // This is an optional field
+    /// Ideal estimate in days, optional if the three-point estimate is given
+    pub estimate: Option<f64>,
+    /// Three-point (PERT) estimate, all of them have to be defined
+    pub optimistic: Option<f64>,
+    pub likely: Option<f64>,
+    pub pessimistic: Option<f64>,
+    /// +/- percentage range of the plain estimate used by the simulation
+    pub uncertainty: Option<f64>,
+    #[serde(default, deserialize_with="parse_dependencies")]
+    pub after: Vec<Dependency>, // This is an optional field
+    /// When a worker has several tasks ready, higher priority goes first
+    pub priority: Option<i32>,
+    /// The task can't start earlier, e.g. waits for a hardware delivery
+    #[serde(default, deserialize_with = "parse_opt_date_entry")]
+    pub not_before: Option<NaiveDate>,
+    /// Finishing later is reported as a warning
+    #[serde(default, deserialize_with = "parse_opt_date_entry")]
+    pub deadline: Option<NaiveDate>,
+    /// The task starts on this day regardless of dependencies and the worker
+    #[serde(default, deserialize_with = "parse_opt_date_entry")]
+    pub fixed_start: Option<NaiveDate>,
+    /// Id of the parent task (e.g. an epic or a phase), parents don't
+    /// have estimates, their dates and effort are rolled up from subtasks
+    pub parent: Option<String>,
+}
+
+impl Task {
+    pub fn three_point(&self) -> Option<(f64, f64, f64)> {
+        Some((self.optimistic?, self.likely?, self.pessimistic?))
+    }
+
+    /// Estimate used for scheduling: PERT expected value if the three-point
+    /// estimate is given, plain estimate otherwise
+    pub fn expected_estimate(&self) -> f64 {
+        match self.three_point() {
+            Some((o, m, p)) => (o + 4.0 * m + p) / 6.0,
+            None => self.estimate.unwrap_or(0.0),
+        }
+    }
+
+    /// PERT standard deviation of the estimate, 0 for plain estimates
+    pub fn estimate_std_dev(&self) -> f64 {
+        match self.three_point() {
+            Some((o, _, p)) => (p - o) / 6.0,
+            None => 0.0,
+        }
+    }
+
+    fn validate(&self, project_start: NaiveDate) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(fixed) = self.fixed_start {
+            if fixed < project_start || self.not_before.is_some_and(|d| fixed < d) {
+                return Err(report_err(format!(
+                    "Task '{}' is fixed to start before the project or its not_before date",
+                    self.id
+                )));
+            }
+        }
+        let points = [self.optimistic, self.likely, self.pessimistic];
+        let defined = points.iter().filter(|p| p.is_some()).count();
+        if defined != 0 && defined != 3 {
+            return Err(report_err(format!(
+                "Task '{}' has incomplete three-point estimate",
+                self.id
+            )));
+        }
+        if let Some((o, m, p)) = self.three_point() {
+            if !(o <= m && m <= p) {
+                return Err(report_err(format!(
+                    "Task '{}' requires optimistic <= likely <= pessimistic",
+                    self.id
+                )));
+            }
+        } else if self.estimate.is_none() {
+            return Err(report_err(format!("Task '{}' has no estimate", self.id)));
+        }
+        if self.uncertainty.is_some_and(|u| !(0.0..=100.0).contains(&u)) {
+            return Err(report_err(format!(
+                "Task '{}' requires uncertainty between 0 and 100",
+                self.id
+            )));
+        }
+        Ok(())
+    }
 }
 
 pub fn parse_vec_str<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
@@ -35,14 +494,64 @@ where
     Ok(ret)
 }
 
-#[derive(Debug, Deserialize)]
+/// Zero-duration point of the project, e.g. a release, other tasks
+/// can depend on it
+#[derive(Debug, Clone, Deserialize)]
+pub struct Milestone {
+    pub id: String,
+    pub name: String,
+    #[serde(default, deserialize_with="parse_dependencies")]
+    pub after: Vec<Dependency>,
+    /// Planned date the forecast is compared against
+    #[serde(default, deserialize_with = "parse_opt_date_entry")]
+    pub target: Option<NaiveDate>,
+}
+
+/// Dependencies given either as a comma separated string
+/// or as a list of strings and tables
+pub fn parse_dependencies<'de, D>(deserializer: D) -> Result<Vec<Dependency>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Entry {
+        Text(String),
+        Table(Dependency),
+    }
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Entries {
+        Text(String),
+        List(Vec<Entry>),
+    }
+    let entries = match Entries::deserialize(deserializer)? {
+        Entries::Text(s) => s.split(',').map(|s| Entry::Text(s.to_string())).collect(),
+        Entries::List(l) => l,
+    };
+    let mut ret = Vec::new();
+    for e in entries {
+        match e {
+            Entry::Text(s) if s.trim().is_empty() => {}
+            Entry::Text(s) => ret.push(Dependency::parse(&s).map_err(serde::de::Error::custom)?),
+            Entry::Table(d) => ret.push(d),
+        }
+    }
+    Ok(ret)
+}
+
+#[derive(Debug, Clone, Deserialize)]
 pub struct Assignment {
     pub task: String,
     pub owner: String,
     pub focus_factor: Option<f64>, // Optional field for overriding focus factor
+    /// Percentage of the task effort done by the owner when a task has
+    /// several owners, the rest is split equally between owners without
+    /// a share; 100 for everybody means pair work
+    pub share: Option<f64>,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct TimeMarker {
     #[serde(deserialize_with="parse_multidate_entry")]
     pub time: Vec<DateObj>,
@@ -50,7 +559,7 @@ pub struct TimeMarker {
     pub color: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct ProjectConfig {
     pub project_name: String,
     #[serde(deserialize_with = "parse_date_entry")]
@@ -59,13 +568,198 @@ pub struct ProjectConfig {
     pub tasks: Vec<Task>,
     pub assignments: Vec<Assignment>,
     pub time_markers: Option<Vec<TimeMarker>>,
+    /// Serialize tasks of the same worker (enabled by default), when
+    /// disabled overlapping tasks are reported as overloaded days
+    pub resource_leveling: Option<bool>,
+    /// Duties rotating between team members, expanded into their
+    /// `recurring_absences`
+    #[serde(default)]
+    pub rotations: Vec<Rotation>,
+    #[serde(default)]
+    pub milestones: Vec<Milestone>,
 }
 
 impl ProjectConfig {
     pub fn from(content: &str) -> Result<ProjectConfig, Box<dyn std::error::Error>> {
-        let config: ProjectConfig = toml::from_str(content)?;
+        let mut config: ProjectConfig = toml::from_str(content)?;
+        for task in &config.tasks {
+            if config.is_parent(task) {
+                config.validate_parent(task)?;
+            } else {
+                task.validate(config.start_date)?;
+            }
+        }
+        for task in &config.tasks {
+            if let Some(p) = &task.parent {
+                if !config.tasks.iter().any(|t| &t.id == p) {
+                    return Err(report_err(format!(
+                        "Task '{}' has unknown parent '{p}'",
+                        task.id
+                    )));
+                }
+                if config.ancestors(task).iter().any(|a| a.id == task.id) {
+                    return Err(report_err(format!("Task '{}' is its own parent", task.id)));
+                }
+            }
+        }
+        for (i, a) in config.assignments.iter().enumerate() {
+            if a.share.is_some_and(|s| s <= 0.0 || s > 100.0) {
+                return Err(report_err(format!(
+                    "Task '{}': share of '{}' must be between 0 and 100",
+                    a.task, a.owner
+                )));
+            }
+            if config.assignments[..i]
+                .iter()
+                .any(|o| o.task == a.task && o.owner == a.owner)
+            {
+                return Err(report_err(format!(
+                    "Task '{}' is assigned to '{}' twice",
+                    a.task, a.owner
+                )));
+            }
+        }
+        for task in &config.tasks {
+            let shares: Vec<Option<f64>> = config
+                .assignments
+                .iter()
+                .filter(|a| a.task == task.id)
+                .map(|a| a.share)
+                .collect();
+            let explicit: f64 = shares.iter().flatten().sum();
+            if shares.iter().any(|s| s.is_none()) {
+                if explicit >= 100.0 - 1e-9 {
+                    return Err(report_err(format!(
+                        "Task '{}': shares of its owners leave nothing for the others",
+                        task.id
+                    )));
+                }
+            } else if !shares.is_empty()
+                && (explicit - 100.0).abs() > 1e-9
+                && shares.iter().any(|s| *s != Some(100.0))
+            {
+                // pair work is 100 for everybody
+                return Err(report_err(format!(
+                    "Task '{}': shares must add up to 100 or all be 100 for pair work",
+                    task.id
+                )));
+            }
+        }
+        for (i, m) in config.milestones.iter().enumerate() {
+            if config.tasks.iter().any(|t| t.id == m.id)
+                || config.milestones[..i].iter().any(|o| o.id == m.id)
+            {
+                return Err(report_err(format!("Milestone id '{}' is not unique", m.id)));
+            }
+        }
+        for member in &config.team {
+            member.validate()?;
+            for a in &member.recurring_absences {
+                a.validate(&member.name)?;
+            }
+        }
+        for rotation in &config.rotations {
+            if rotation.members.is_empty() {
+                return Err(report_err(format!("Rotation '{}' has no members", rotation.name)));
+            }
+            for (member, absence) in rotation.absences() {
+                absence.validate(member)?;
+                match config.team.iter_mut().find(|m| m.name == member) {
+                    Some(m) => m.recurring_absences.push(absence),
+                    None => {
+                        return Err(report_err(format!(
+                            "Rotation '{}': worker '{member}' not defined",
+                            rotation.name
+                        )))
+                    }
+                }
+            }
+        }
         Ok(config)
     }
+
+    /// Assignments of the task with the part of the effort (0 - 1.0)
+    /// done by every owner
+    pub fn shares(&self, task_id: &str) -> Vec<(&Assignment, f64)> {
+        let assignments: Vec<&Assignment> =
+            self.assignments.iter().filter(|a| a.task == task_id).collect();
+        let explicit: f64 = assignments.iter().filter_map(|a| a.share).sum();
+        let implicit = assignments.iter().filter(|a| a.share.is_none()).count();
+        let rest = (100.0 - explicit) / implicit.max(1) as f64;
+        assignments
+            .into_iter()
+            .map(|a| (a, a.share.unwrap_or(rest) / 100.0))
+            .collect()
+    }
+
+    /// True if the task has subtasks
+    pub fn is_parent(&self, task: &Task) -> bool {
+        self.tasks.iter().any(|t| t.parent.as_ref() == Some(&task.id))
+    }
+
+    /// Parents of the task, the nearest one first
+    pub fn ancestors(&self, task: &Task) -> Vec<&Task> {
+        let mut ret = Vec::new();
+        let mut parent = task.parent.as_ref();
+        while let Some(p) = parent {
+            let Some(t) = self.tasks.iter().find(|t| &t.id == p) else {
+                break;
+            };
+            if ret.len() > self.tasks.len() {
+                // circular parents, reported by the validation
+                break;
+            }
+            ret.push(t);
+            parent = t.parent.as_ref();
+        }
+        ret
+    }
+
+    fn validate_parent(&self, task: &Task) -> Result<(), Box<dyn std::error::Error>> {
+        let scheduling = [
+            task.estimate,
+            task.optimistic,
+            task.likely,
+            task.pessimistic,
+            task.uncertainty,
+        ];
+        let dates = [task.not_before, task.deadline, task.fixed_start];
+        if scheduling.iter().any(|v| v.is_some())
+            || dates.iter().any(|d| d.is_some())
+            || task.priority.is_some()
+        {
+            return Err(report_err(format!(
+                "Task '{}' has subtasks, its estimate and dates come from them",
+                task.id
+            )));
+        }
+        if self.assignments.iter().any(|a| a.task == task.id) {
+            return Err(report_err(format!(
+                "Task '{}' has subtasks, assign them instead",
+                task.id
+            )));
+        }
+        Ok(())
+    }
+
+    /// Imports holidays of team members from their iCalendar files
+    pub fn load_ics(&mut self, base_dir: &std::path::Path) -> Result<(), Box<dyn std::error::Error>> {
+        for member in self.team.iter_mut() {
+            if let Some(file) = &member.holidays_ics {
+                for event in calendar::load_ics(&base_dir.join(file))? {
+                    match event.date {
+                        IcsDate::Once(d) => member.holidays.push(d),
+                        IcsDate::Yearly(y) => member.recurring_holidays.push(y),
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn report_err(msg: String) -> Box<ProjectError> {
+    Box::new(ProjectError(msg))
 }
 
 #[cfg(test)]
@@ -93,4 +787,224 @@ mod tests {
         assert_eq!(proj.project_name, "Game development");
     }
 
+    #[test]
+    fn three_point_estimate_test() {
+        let proj = ProjectConfig::from(
+            r#"
+project_name = "PERT"
+start_date = "2024-10-01"
+team = []
+tasks = [
+    {id="T1", name="Plain", estimate=2},
+    {id="T2", name="PERT", optimistic=1, likely=2, pessimistic=9},
+]
+assignments = []
+"#,
+        )
+        .unwrap();
+        assert_eq!(proj.tasks[0].expected_estimate(), 2.0);
+        assert_eq!(proj.tasks[0].estimate_std_dev(), 0.0);
+        assert_eq!(proj.tasks[1].expected_estimate(), 3.0);
+        assert!((proj.tasks[1].estimate_std_dev() - 8.0 / 6.0).abs() < 1e-9);
+
+        let incomplete = ProjectConfig::from(
+            r#"
+project_name = "PERT"
+start_date = "2024-10-01"
+team = []
+tasks = [{id="T1", name="Broken", optimistic=1, likely=2}]
+assignments = []
+"#,
+        );
+        assert!(incomplete.is_err());
+    }
+
+    #[test]
+    fn uncertainty_test() {
+        let proj = |member: &str, task: &str| {
+            ProjectConfig::from(&format!(
+                "project_name = \"Uncertainty\"\nstart_date = \"2024-10-01\"\n\
+                 team = [{{name=\"Ala\", base_calendar=\"cal.toml\", focus_factor=1.0, holidays=\"\", other_duties=\"\", {member}}}]\n\
+                 tasks = [{{id=\"T\", name=\"Task\", estimate=2, {task}}}]\nassignments = []\n"
+            ))
+        };
+        assert!(proj("focus_factor_uncertainty=10", "uncertainty=100").is_ok());
+        assert!(proj("focus_factor_uncertainty=10", "uncertainty=200").is_err());
+        assert!(proj("focus_factor_uncertainty=10", "uncertainty=-5").is_err());
+        assert!(proj("focus_factor_uncertainty=150", "uncertainty=10").is_err());
+    }
+
+    #[test]
+    fn working_hours_test() {
+        let proj = |hours: &str| {
+            ProjectConfig::from(&format!(
+                "project_name = \"Hours\"\nstart_date = \"2024-10-01\"\n\
+                 team = [{{name=\"Ala\", base_calendar=\"cal.toml\", focus_factor=1.0, holidays=\"\", other_duties=\"\", {hours}}}]\n\
+                 tasks = []\nassignments = []\n"
+            ))
+        };
+        assert!(proj("working_hrs_in_day=0, working_hrs_by_weekday={monday=4}").is_ok());
+        assert!(proj("working_hrs_by_weekday={monday=0, friday=0}").is_ok());
+        assert!(proj("working_hrs_in_day=0").is_err());
+        assert!(proj("working_hrs_by_weekday={monday=0, tuesday=0, wednesday=0, thursday=0, friday=0, saturday=0, sunday=0}").is_err());
+    }
+
+    #[test]
+    fn commitments_test() {
+        let proj = |commitments: &str| {
+            ProjectConfig::from(&format!(
+                "project_name = \"Commitments\"\nstart_date = \"2024-10-01\"\n\
+                 team = [{{name=\"Ala\", base_calendar=\"cal.toml\", focus_factor=1.0, holidays=\"\", other_duties=\"\", working_hrs_in_day=6, commitments=[{commitments}]}}]\n\
+                 tasks = []\nassignments = []\n"
+            ))
+        };
+        assert!(proj(r#"{name="Support", percent=50}, {name="Planning", hours=2}"#).is_ok());
+        assert!(proj(r#"{name="Other project", percent=100, to="2024-10-31"}"#).is_ok());
+        assert!(proj(r#"{name="Other project", percent=100, weekdays=["monday"]}"#).is_ok());
+        assert!(proj(r#"{name="Other project", percent=100}"#).is_err());
+        assert!(proj(r#"{name="Support", percent=50}, {name="Planning", hours=3}"#).is_err());
+        assert!(proj(r#"{name="Support", percent=120}"#).is_err());
+    }
+
+    #[test]
+    fn dependencies_test() {
+        let proj = ProjectConfig::from(
+            r#"
+project_name = "Dependencies"
+start_date = "2024-10-01"
+team = []
+tasks = [
+    {id="T1", name="Design", estimate=2},
+    {id="T2", name="Implementation", estimate=2, after="T1:ss+2d, T1:FF"},
+    {id="T3", name="Tests", estimate=2, after=["T2:-1.5d", {task="T1", kind="SF"}]},
+]
+assignments = []
+"#,
+        )
+        .unwrap();
+        let after: Vec<String> = proj.tasks[1..]
+            .iter()
+            .flat_map(|t| t.after.iter().map(|d| d.to_string()))
+            .collect();
+        assert_eq!(after, ["T1:SS+2d", "T1:FF", "T2:FS-1.5d", "T1:SF"]);
+        assert_eq!(proj.tasks[1].after[0].lag, 2.0);
+        assert!(Dependency::parse("T1:XX").is_err());
+        assert!(Dependency::parse("T1:SS+xd").is_err());
+    }
+
+    #[test]
+    fn parents_test() {
+        let proj = |tasks: &str| {
+            ProjectConfig::from(&format!(
+                "project_name = \"WBS\"\nstart_date = \"2024-10-01\"\nteam = []\ntasks = [{tasks}]\nassignments = []\n"
+            ))
+        };
+        let ok = proj(r#"{id="P", name="Phase"}, {id="S", name="Sub", parent="P"}, {id="T", name="Task", estimate=1, parent="S"}"#)
+            .unwrap();
+        assert!(ok.is_parent(&ok.tasks[0]) && !ok.is_parent(&ok.tasks[2]));
+        let ancestors: Vec<&str> = ok
+            .ancestors(&ok.tasks[2])
+            .iter()
+            .map(|t| t.id.as_str())
+            .collect();
+        assert_eq!(ancestors, ["S", "P"]);
+        assert!(proj(r#"{id="T", name="Task", estimate=1, parent="X"}"#).is_err());
+        assert!(proj(r#"{id="P", name="Phase", estimate=1}, {id="T", name="Task", estimate=1, parent="P"}"#).is_err());
+        assert!(proj(r#"{id="A", name="A", parent="B"}, {id="B", name="B", parent="A"}"#).is_err());
+        let assigned = ProjectConfig::from(
+            r#"
+project_name = "WBS"
+start_date = "2024-10-01"
+team = []
+tasks = [{id="P", name="Phase"}, {id="T", name="Task", estimate=1, parent="P"}]
+assignments = [{task="P", owner="Ala"}]
+"#,
+        );
+        assert!(assigned.is_err());
+    }
+
+    #[test]
+    fn shares_test() {
+        let proj = |assignments: &str| {
+            ProjectConfig::from(&format!(
+                "project_name = \"Shares\"\nstart_date = \"2024-10-01\"\nteam = []\n\
+                 tasks = [{{id=\"T\", name=\"Task\", estimate=4}}]\nassignments = [{assignments}]\n"
+            ))
+        };
+        let split = proj(
+            r#"{task="T", owner="A", share=50}, {task="T", owner="B"}, {task="T", owner="C"}"#,
+        )
+        .unwrap();
+        let shares: Vec<f64> = split.shares("T").iter().map(|(_, s)| *s).collect();
+        assert_eq!(shares, [0.5, 0.25, 0.25]);
+        assert!(proj(r#"{task="T", owner="A", share=120}"#).is_err());
+        assert!(proj(
+            r#"{task="T", owner="A", share=100}, {task="T", owner="B", share=100}"#
+        )
+        .is_ok());
+        assert!(proj(
+            r#"{task="T", owner="A", share=30}, {task="T", owner="B", share=70}"#
+        )
+        .is_ok());
+        assert!(proj(r#"{task="T", owner="A", share=50}"#).is_err());
+        assert!(proj(
+            r#"{task="T", owner="A", share=70}, {task="T", owner="B", share=70}"#
+        )
+        .is_err());
+        assert!(proj(r#"{task="T", owner="A", share=100}, {task="T", owner="B"}"#).is_err());
+        assert!(proj(
+            r#"{task="T", owner="A", share=60}, {task="T", owner="B", share=40}, {task="T", owner="C"}"#
+        )
+        .is_err());
+        assert!(proj(r#"{task="T", owner="A"}, {task="T", owner="A"}"#).is_err());
+    }
+
+    #[test]
+    fn recurring_absences_test() {
+        let proj = ProjectConfig::from(
+            r#"
+project_name = "Rotations"
+start_date = "2024-10-01"
+team = [{name="Ala", base_calendar="cal.toml", focus_factor=1.0, holidays="", other_duties="", recurring_absences=[
+    {name="On-call", repeat="weekly", interval=2, from="2024-10-07", days=5},
+    {name="Day off", repeat="monthly", weekday="friday", nth=1, kind="holidays"},
+    {name="Ceremonies", repeat="weekly", from="2024-10-01", part="2h"},
+    {name="Inventory", repeat="monthly", day=30, days=3, from="2024-10-01", to="2024-12-31"},
+]}]
+tasks = []
+assignments = []
+"#,
+        )
+        .unwrap();
+        let date = |s| NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap();
+        let [on_call, day_off, ceremonies, inventory] = &proj.team[0].recurring_absences[..] else {
+            panic!("4 recurring absences expected");
+        };
+        assert_eq!(on_call.on(&date("2024-10-06")), None);
+        assert!(on_call.on(&date("2024-10-11")).is_some());
+        assert_eq!(on_call.on(&date("2024-10-14")), None);
+        assert!(on_call.on(&date("2024-10-21")).is_some());
+        assert_eq!(day_off.on(&date("2024-11-01")), Some(DateObj::Date(date("2024-11-01"))));
+        assert_eq!(day_off.on(&date("2024-11-08")), None);
+        assert_eq!(
+            ceremonies.on(&date("2024-10-15")),
+            Some(DateObj::PartOfDay(date("2024-10-15"), calendar::DayPart::Hours(2)))
+        );
+        assert!(inventory.on(&date("2024-11-01")).is_some());
+        assert_eq!(inventory.on(&date("2024-11-02")), None);
+        assert_eq!(inventory.on(&date("2025-01-01")), None);
+
+        let invalid = ProjectConfig::from(
+            r#"
+project_name = "Rotations"
+start_date = "2024-10-01"
+team = [{name="Ala", base_calendar="cal.toml", focus_factor=1.0, holidays="", other_duties="", recurring_absences=[
+    {name="On-call", repeat="weekly", days=5},
+]}]
+tasks = []
+assignments = []
+"#,
+        );
+        assert!(invalid.is_err());
+    }
 }
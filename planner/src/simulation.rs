@@ -0,0 +1,210 @@
+use crate::{calendar, cfg, gantt_builder, project};
+use chrono::NaiveDate;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::{BTreeMap, HashMap};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Percentiles {
+    pub p50: NaiveDate,
+    pub p80: NaiveDate,
+    pub p95: NaiveDate,
+}
+
+impl Percentiles {
+    fn from(dates: &mut [NaiveDate]) -> Percentiles {
+        dates.sort();
+        let at = |p: f64| {
+            let i = (p * dates.len() as f64).ceil() as usize;
+            dates[i.clamp(1, dates.len()) - 1]
+        };
+        Percentiles {
+            p50: at(0.5),
+            p80: at(0.8),
+            p95: at(0.95),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct SimulationResult {
+    pub iterations: usize,
+    pub seed: u64,
+    pub project_end: Percentiles,
+    /// <id, date percentiles> for milestones of the project, or for tasks
+    /// no other task depends on if the project has no milestones
+    pub milestones: Vec<(String, Percentiles)>,
+    /// <project end date, number of iterations>
+    pub histogram: BTreeMap<NaiveDate, usize>,
+}
+
+impl std::fmt::Display for SimulationResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        writeln!(
+            f,
+            "Simulation: {} iterations (seed: {})",
+            self.iterations, self.seed
+        )?;
+        writeln!(f, "{:<20} {:>12} {:>12} {:>12}", "", "P50", "P80", "P95")?;
+        let mut row = |name: &str, p: &Percentiles| {
+            writeln!(f, "{name:<20} {:>12} {:>12} {:>12}", p.p50, p.p80, p.p95)
+        };
+        row("Project end", &self.project_end)?;
+        for (id, p) in &self.milestones {
+            row(id, p)?;
+        }
+        writeln!(f, "\nProject end histogram:")?;
+        let max = self.histogram.values().copied().max().unwrap_or(1);
+        for (d, count) in &self.histogram {
+            let bar = "#".repeat((count * 50).div_ceil(max));
+            writeln!(f, "{d} {count:>6} {bar}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Samples from a triangular distribution
+fn triangular(rng: &mut StdRng, min: f64, mode: f64, max: f64) -> f64 {
+    if max - min < 1e-9 {
+        return mode;
+    }
+    let u: f64 = rng.gen();
+    let fc = (mode - min) / (max - min);
+    if u < fc {
+        min + (u * (max - min) * (mode - min)).sqrt()
+    } else {
+        max - ((1.0 - u) * (max - min) * (max - mode)).sqrt()
+    }
+}
+
+/// Samples from a +/- percentage range around the value
+fn around(rng: &mut StdRng, value: f64, percentage: Option<f64>) -> f64 {
+    match percentage {
+        Some(p) => {
+            let delta = value * p / 100.0;
+            triangular(rng, value - delta, value, value + delta)
+        }
+        None => value,
+    }
+}
+
+/// Project with sampled task estimates and focus factors
+fn sample_project(rng: &mut StdRng, proj: &project::ProjectConfig) -> project::ProjectConfig {
+    let parents: Vec<bool> = proj.tasks.iter().map(|t| proj.is_parent(t)).collect();
+    let mut proj = proj.clone();
+    for (task, _) in proj.tasks.iter_mut().zip(parents).filter(|(_, p)| !p) {
+        let estimate = match task.three_point() {
+            Some((o, m, p)) => triangular(rng, o, m, p),
+            None => around(rng, task.estimate.unwrap_or(0.0), task.uncertainty),
+        };
+        task.estimate = Some(estimate.max(0.0));
+        task.optimistic = None;
+        task.likely = None;
+        task.pessimistic = None;
+    }
+    for worker in proj.team.iter_mut() {
+        let factor = around(rng, 1.0, worker.focus_factor_uncertainty).max(0.01);
+        worker.focus_factor *= factor;
+        for a in proj.assignments.iter_mut().filter(|a| a.owner == worker.name) {
+            a.focus_factor = a.focus_factor.map(|f| f * factor);
+        }
+    }
+    proj
+}
+
+/// Runs the scheduler `iterations` times with sampled task durations
+/// and focus factors.
+pub fn simulate(
+    cfg: &cfg::Config,
+    proj: &project::ProjectConfig,
+    calendars: &HashMap<&String, calendar::BusinessDaysCalendar>,
+    iterations: usize,
+    seed: u64,
+) -> Result<SimulationResult, Box<dyn std::error::Error>> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let milestone_ids: Vec<&String> = if proj.milestones.is_empty() {
+        // top-level tasks and groups nothing depends on
+        proj.tasks
+            .iter()
+            .filter(|t| {
+                t.parent.is_none()
+                    && !proj
+                        .tasks
+                        .iter()
+                        .any(|o| o.after.iter().any(|d| d.task == t.id))
+            })
+            .map(|t| &t.id)
+            .collect()
+    } else {
+        proj.milestones.iter().map(|m| &m.id).collect()
+    };
+    let mut project_ends = Vec::with_capacity(iterations);
+    let mut milestone_ends = vec![Vec::with_capacity(iterations); milestone_ids.len()];
+    let mut histogram = BTreeMap::new();
+    for _ in 0..iterations.max(1) {
+        let sampled = sample_project(&mut rng, proj);
+        let gantt = gantt_builder::process(cfg, &sampled, calendars)?;
+        project_ends.push(gantt.project_ends);
+        *histogram.entry(gantt.project_ends).or_insert(0) += 1;
+        for (id, ends) in milestone_ids.iter().zip(milestone_ends.iter_mut()) {
+            if let Some(t) = gantt.tasks.iter().find(|t| &&t.id == id) {
+                ends.push(t.end_on);
+            } else if let Some(g) = gantt.groups.iter().find(|g| &&g.id == id) {
+                ends.push(g.end_on);
+            } else if let Some(m) = gantt.milestones.iter().find(|m| &&m.id == id) {
+                ends.push(m.date);
+            }
+        }
+    }
+    let milestones = milestone_ids
+        .into_iter()
+        .zip(milestone_ends.iter_mut())
+        .map(|(id, ends)| (id.clone(), Percentiles::from(ends)))
+        .collect();
+    Ok(SimulationResult {
+        iterations: project_ends.len(),
+        seed,
+        project_end: Percentiles::from(&mut project_ends),
+        milestones,
+        histogram,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gantt_builder::tests::{default_cfg, example_calendars};
+
+    #[test]
+    fn test_simulation_is_reproducible() {
+        let cfg = default_cfg();
+        let proj = project::ProjectConfig::from(
+            r#"
+project_name = "Simulation"
+start_date = "2024-10-01"
+team = [
+    { name="Ala", base_calendar="calendar_pl.toml", focus_factor=0.8, focus_factor_uncertainty=10, holidays="", other_duties=""},
+]
+tasks = [
+    {id="A", name="Task A", optimistic=1, likely=2, pessimistic=6},
+    {id="B", name="Task B", estimate=3, uncertainty=20, after="A"},
+]
+assignments = [
+    {task="A", owner="Ala"},
+    {task="B", owner="Ala"},
+]
+"#,
+        )
+        .unwrap();
+        let calendars = example_calendars();
+        let first = simulate(&cfg, &proj, &calendars, 500, 42).unwrap();
+        let second = simulate(&cfg, &proj, &calendars, 500, 42).unwrap();
+        assert_eq!(first.project_end, second.project_end);
+        assert_eq!(first.histogram, second.histogram);
+        assert_eq!(first.histogram.values().sum::<usize>(), 500);
+        assert!(first.project_end.p50 <= first.project_end.p80);
+        assert!(first.project_end.p80 <= first.project_end.p95);
+        assert_eq!(first.milestones.len(), 1);
+        assert_eq!(first.milestones[0].0, "B");
+    }
+}